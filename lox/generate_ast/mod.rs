@@ -12,19 +12,31 @@ pub fn generate_ast(output_dir: &str) -> io::Result<()> {
     define_ast(
         output_dir,
         "Expr",
-        &["errors", "entities", "rc"],
+        &["errors", "entities", "pattern", "stmt", "rc"],
         &[
             "Assign   : Token name, Rc<Expr> value",
             "Binary   : Rc<Expr> left, Token operator, Rc<Expr> right",
-            "Call     : Rc<Expr> callee, Token paren, Vec<Rc<Expr>> arguments",
+            "Call     : Rc<Expr> callee, Token paren, Vec<Rc<Expr>> arguments, Vec<Token> names, Vec<Rc<Expr>> named",
+            "Compound : Rc<Expr> target, Token operator, Rc<Expr> value",
+            "Conditional : Rc<Expr> condition, Rc<Expr> then_branch, Rc<Expr> else_branch",
             "Get      : Rc<Expr> literalvalue, Token name",
             "Grouping : Rc<Expr> expression",
+            "Index    : Rc<Expr> literalvalue, Token bracket, Rc<Expr> index",
+            "IndexSet : Rc<Expr> literalvalue, Token bracket, Rc<Expr> index, Rc<Expr> value",
+            "Lambda   : Rc<FunctionStmt> declaration",
+            "List     : Token bracket, Vec<Rc<Expr>> elements",
             "Literal  : Option<LiteralValue> value",
             "Logical  : Rc<Expr> left, Token operator, Rc<Expr> right",
+            "Optional : Rc<Expr> expression",
+            "OptionalGet : Rc<Expr> literalvalue, Token name",
+            "Map      : Token brace, Vec<Rc<Expr>> keys, Vec<Rc<Expr>> values",
+            "Match    : Token keyword, Rc<Expr> subject, Rc<Vec<MatchArm>> arms",
             "Set      : Rc<Expr> literalvalue, Token name, Rc<Expr> value",
+            "Stringify : Rc<Expr> expression",
             "Super    : Token keyword, Token method",
             "This     : Token keyword",
             "Unary    : Token operator, Rc<Expr> right",
+            "Update   : Rc<Expr> target, Token operator, bool prefix",
             "Variable : Token name",
         ],
     )?;
@@ -34,15 +46,27 @@ pub fn generate_ast(output_dir: &str) -> io::Result<()> {
         &["errors", "expr", "entities", "rc"],
         &[
             "Block      : Rc<Vec<Rc<Stmt>>> statements",
-            "Class      : Token name, Option<Rc<Expr>> superclass, Rc<Vec<Rc<Stmt>>> methods",
+            "Class      : Token name, Option<Rc<Expr>> superclass, Rc<Vec<Rc<Expr>>> traits, Rc<Vec<Rc<Stmt>>> methods, Rc<Vec<Rc<Stmt>>> static_methods, Rc<Vec<Rc<Stmt>>> static_fields, Rc<Vec<Rc<Stmt>>> getters, Rc<Vec<Rc<Stmt>>> setters",
+            "Trait      : Token name, Rc<Vec<Rc<Stmt>>> methods, Rc<Vec<Token>> required",
+            "Enum       : Token name, Rc<Vec<Token>> variants, Rc<Vec<Option<Vec<Token>>>> fields",
             "Break      : Token token",
+            "Continue   : Token token",
             "Expression : Rc<Expr> expression",
-            "Function   : Token name, Rc<Vec<Token>> params, Rc<Vec<Rc<Stmt>>> body",
+            "Function   : Token name, Rc<Vec<Token>> params, Rc<Vec<Option<Rc<Expr>>>> defaults, Option<Token> rest, Rc<Vec<Rc<Stmt>>> body, bool is_generator",
             "If         : Rc<Expr> condition, Rc<Stmt> then_branch, Option<Rc<Stmt>> else_branch",
             "Print      : Rc<Expr> expression",
             "Return     : Token keyword, Option<Rc<Expr>> value",
+            "Throw      : Token keyword, Rc<Expr> value",
+            "Yield      : Token keyword, Option<Rc<Expr>> value",
+            "Import     : Token keyword, Token path, Option<Token> alias, Rc<Vec<Token>> names",
+            "Export     : Token keyword, Rc<Stmt> declaration",
+            "Try        : Rc<Vec<Rc<Stmt>>> body, Option<Token> catch_name, Option<Rc<Vec<Rc<Stmt>>>> catch_body, Option<Rc<Vec<Rc<Stmt>>>> finally_body",
             "Var        : Token name, Option<Rc<Expr>> initializer",
-            "While      : Rc<Expr> condition, Rc<Stmt> body",
+            "Const      : Token name, Rc<Expr> initializer",
+            "Destructure : Token pattern, Vec<Token> names, Rc<Expr> initializer",
+            "MultiAssign : Vec<Rc<Expr>> targets, Token equals, Vec<Rc<Expr>> values",
+            "While      : Rc<Expr> condition, Rc<Stmt> body, Option<Rc<Expr>> increment",
+            "ForIn      : Token name, Token keyword, Rc<Expr> iterable, Rc<Stmt> body",
         ],
     )?;
     Ok(())
@@ -21,8 +21,7 @@ impl Debug for Callable {
 
 impl Display for Callable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "<Callable>");
-        Ok(())
+        write!(f, "<Callable>")
     }
 }
 
@@ -42,6 +41,53 @@ pub trait LoxCallable {
         arguments: Vec<LiteralValue>,
         klass: Option<Rc<LoxClass>>,
     ) -> Result<LiteralValue, LoxResult>;
-    fn arity(&self) -> usize;
-    
+    fn arity(&self) -> Arity;
+
+    // Callables opt in to named arguments by overriding this; by default
+    // they are rejected. It is called instead of `call` only when there
+    // are named arguments, and the arity has not been checked.
+    fn call_named(
+        &self,
+        interpreter: &Interpreter,
+        arguments: Vec<LiteralValue>,
+        named: Vec<(Token, LiteralValue)>,
+        klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        if let Some((name, _)) = named.first() {
+            Err(LoxResult::runtime_error(
+                name,
+                "This function does not take named arguments.",
+            ))
+        } else {
+            self.call(interpreter, arguments, klass)
+        }
+    }
+}
+
+// How many arguments a callable accepts. Parameters with defaults lower
+// the minimum, and a rest parameter removes the maximum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    pub fn exact(n: usize) -> Self {
+        Self { min: n, max: Some(n) }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{max}"),
+            Some(max) => write!(f, "{} to {max}", self.min),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
 }
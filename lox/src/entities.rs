@@ -2,18 +2,23 @@ use core::fmt;
 use std::rc::Rc;
 //use std::ops::*;
 
-use crate::{ lox_class::LoxClass, lox_instance::LoxInstance,native_functions::*};
+use crate::{ lox_class::LoxClass, lox_instance::LoxInstance, lox_list::LoxList, lox_map::LoxMap, lox_module::LoxModule, lox_range::LoxRange, lox_generator::LoxGenerator, lox_bigint::BigInt, lox_enum::*, lox_trait::LoxTrait, lox_decimal::Decimal, native_functions::*};
 use crate::lox_function::*;
 use std::fmt::Display;
-#[allow(non_camel_case_types)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TokenType {
     // Single-character tokens.
     Break,
+    Continue,
     LEFT_PAREN,
     RIGHT_PAREN,
     LEFT_BRACE,
     RIGHT_BRACE,
+    LEFT_BRACKET,
+    RIGHT_BRACKET,
+    COLON,
+    QUESTION,
     COMMA,
     DOT,
     MINUS,
@@ -21,20 +26,41 @@ pub enum TokenType {
     SEMICOLON,
     SLASH,
     STAR,
+    PERCENT,
+    AMPERSAND,
+    PIPE,
+    CARET,
+    TILDE,
 
     // One or two character tokens.
     BANG,
     BANG_EQUAL,
     EQUAL,
     EQUAL_EQUAL,
+    ARROW,
     GREATER,
     GREATER_EQUAL,
     LESS,
     LESS_EQUAL,
+    STAR_STAR,
+    TILDE_SLASH,
+    LESS_LESS,
+    GREATER_GREATER,
+    QUESTION_QUESTION,
+    QUESTION_DOT,
+    DOT_DOT_DOT,
+    PLUS_EQUAL,
+    MINUS_EQUAL,
+    STAR_EQUAL,
+    SLASH_EQUAL,
+    PERCENT_EQUAL,
+    PLUS_PLUS,
+    MINUS_MINUS,
 
     // Literals.
     IDENTIFIER,
     STRING,
+    INTERPOLATION,
     NUMBER,
 
     // Keywords.
@@ -54,19 +80,47 @@ pub enum TokenType {
     TRUE,
     VAR,
     WHILE,
+    THROW,
+    TRY,
+    CATCH,
+    FINALLY,
+    STATIC,
+    IMPORT,
+    FROM,
+    AS,
+    EXPORT,
+    MATCH,
+    CONST,
+    IN,
+    YIELD,
+    ENUM,
+    TRAIT,
+    WITH,
 
     EOF,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LiteralValue {
+    Int(i64),
+    BigInt(Rc<BigInt>),
     Num(f64),
+    Decimal(Rc<Decimal>),
     Str(String),
     Bool(bool),
     Func(Rc<LoxFunction>),
     Class(Rc<LoxClass>),
+    Trait(Rc<LoxTrait>),
     Instance(Rc<LoxInstance>),
     Native(Rc<LoxNative>),
+    List(Rc<LoxList>),
+    Map(Rc<LoxMap>),
+    Module(Rc<LoxModule>),
+    Range(Rc<LoxRange>),
+    Generator(Rc<LoxGenerator>),
+    Enum(Rc<LoxEnum>),
+    Variant(Rc<LoxVariant>),
+    EnumValue(Rc<LoxEnumValue>),
     Nil,
     ArithmeticError,
     NumsOrStringsError,
@@ -74,6 +128,12 @@ pub enum LiteralValue {
 impl fmt::Display for LiteralValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            LiteralValue::Int(x) => write!(f, "{x}"),
+            LiteralValue::BigInt(x) => write!(f, "{x}"),
+            LiteralValue::Decimal(x) => write!(f, "{x}"),
+            // A whole float keeps its ".0" so that it can't be mistaken for
+            // an integer.
+            LiteralValue::Num(x) if x.is_finite() && x.fract() == 0.0 => write!(f, "{x:.1}"),
             LiteralValue::Num(x) => write!(f, "{x}"),
             LiteralValue::Str(x) => write!(f, "{x}"),
             LiteralValue::Nil => write!(f, "nil"),
@@ -81,13 +141,42 @@ impl fmt::Display for LiteralValue {
             LiteralValue::Bool(false) => write!(f, "false"),
             LiteralValue::Func(func) => write!(f, "{}", func),
             LiteralValue::Class(c) => write!(f, "{}", c),
+            LiteralValue::Trait(t) => write!(f, "{t}"),
             LiteralValue::Native(n) => write!(f, "{n}"),
-            LiteralValue::Instance(i) => write!(f, "<Instance of {}", i),
+            LiteralValue::List(l) => write!(f, "{l}"),
+            LiteralValue::Map(m) => write!(f, "{m}"),
+            LiteralValue::Module(m) => write!(f, "{m}"),
+            LiteralValue::Range(r) => write!(f, "{r}"),
+            LiteralValue::Generator(g) => write!(f, "{g}"),
+            LiteralValue::Enum(e) => write!(f, "{e}"),
+            LiteralValue::Variant(v) => write!(f, "<variant {v}>"),
+            LiteralValue::EnumValue(v) => write!(f, "{v}"),
+            LiteralValue::Instance(i) => write!(f, "<{i}>"),
              _ => panic!("Should not be trying to print this"),
         }
     }
 }
 
+impl LiteralValue {
+    // An integer is only big when it doesn't fit in an i64, so each integer
+    // has a single representation.
+    pub fn integer(n: BigInt) -> LiteralValue {
+        match n.to_i64() {
+            Some(n) => LiteralValue::Int(n),
+            None => LiteralValue::BigInt(Rc::new(n)),
+        }
+    }
+
+    // How a value is shown when nested inside a collection, where strings
+    // are quoted so that `["1", 1]` does not print as `[1, 1]`.
+    pub fn repr(&self) -> String {
+        match self {
+            LiteralValue::Str(s) => format!("\"{s}\""),
+            _ => self.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     token_type: TokenType,
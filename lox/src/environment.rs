@@ -1,6 +1,6 @@
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 //use crate::entities;
 use crate::entities::*;
@@ -10,6 +10,7 @@ use crate::errors::*;
 pub struct Environment {
     values: HashMap<String, LiteralValue>,
     enclosing: Option<Rc<RefCell<Environment>>>,
+    constants: HashSet<String>,
 }
 
 impl Environment {
@@ -17,6 +18,7 @@ impl Environment {
         Environment {
             values: HashMap::new(),
             enclosing: None,
+            constants: HashSet::new(),
         }
     }
 
@@ -24,14 +26,22 @@ impl Environment {
         Environment {
             values: HashMap::new(),
             enclosing: Some(enclosing),
+            constants: HashSet::new(),
         }
     }
 
     pub fn assign(&mut self, name: &Token, value: LiteralValue) -> Result<(), LoxResult> {
         if let Entry::Occupied(mut literal_value) = self.values.entry(name.as_string()) {
+            if self.constants.contains(literal_value.key()) {
+                return Err(LoxResult::runtime_error(
+                    name,
+                    &format!("Cannot assign to constant '{}'.", name.as_string()),
+                ));
+            }
             literal_value.insert(value);
             return Ok(());
-        } if let Some(enclosing) = &self.enclosing {
+        }
+        if let Some(enclosing) = &self.enclosing {
             enclosing.borrow_mut().assign(name, value)
         } else {
             Err(LoxResult::runtime_error(
@@ -41,8 +51,18 @@ impl Environment {
         }
     }
 
+    // Redeclaring a name replaces it, constant or not, just as a global
+    // var can be declared twice.
     pub fn define(&mut self, name: &str, value: LiteralValue) {
+        self.constants.remove(name);
+        self.values.insert(name.to_string(), value);
+    }
+
+    // The resolver rejects assignments to local constants before the program
+    // runs, so only assign() has to check, and only globals reach it.
+    pub fn define_constant(&mut self, name: &str, value: LiteralValue) {
         self.values.insert(name.to_string(), value);
+        self.constants.insert(name.to_string());
     }
 
     pub fn get(&self, name: &Token) -> Result<LiteralValue, LoxResult> {
@@ -59,6 +79,46 @@ impl Environment {
         }
     }
 
+    // Variables the resolver leaves unresolved are globals. They live in the
+    // outermost environment of the chain, which belongs to the file the
+    // code was loaded from.
+    pub fn get_global(&self, name: &Token) -> Result<LiteralValue, LoxResult> {
+        if let Some(enclosing) = &self.enclosing {
+            enclosing.borrow().get_global(name)
+        } else {
+            self.get(name)
+        }
+    }
+
+    pub fn assign_global(&mut self, name: &Token, value: LiteralValue) -> Result<(), LoxResult> {
+        if let Some(enclosing) = &self.enclosing {
+            enclosing.borrow_mut().assign_global(name, value)
+        } else {
+            self.assign(name, value)
+        }
+    }
+
+    // Fails just as assign_global() would, without assigning, so that a
+    // statement with several targets can fail before any of them changes.
+    pub fn check_assign_global(&self, name: &Token) -> Result<(), LoxResult> {
+        if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow().check_assign_global(name);
+        }
+        if self.constants.contains(&name.as_string()) {
+            Err(LoxResult::runtime_error(
+                name,
+                &format!("Cannot assign to constant '{}'.", name.as_string()),
+            ))
+        } else if !self.values.contains_key(&name.as_string()) {
+            Err(LoxResult::runtime_error(
+                name,
+                &format!("Undefined variable '{}'.", name.as_string()),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn get_at(&self, distance: usize, name: &str) -> Result<LiteralValue, LoxResult> {
         if distance == 0 {
             Ok(self.values.get(name).unwrap().clone())
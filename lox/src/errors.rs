@@ -17,7 +17,11 @@ pub enum LoxResult {
     Error { line: usize, message: String },
     SystemError { message: String },
     ReturnValue { value: LiteralValue },
+    Yield { value: LiteralValue },
+    Throw { token: Token, value: LiteralValue },
     Break,
+    Continue,
+    NilChain,
     Fail,
 }
 
@@ -45,23 +49,27 @@ impl LoxResult {
         err
     }
 
+    // Runtime errors can be caught by a try statement, so unlike scan and
+    // parse errors they are only reported once they reach the top level.
     pub fn runtime_error(token: &Token, message: &str) -> LoxResult {
-        let err = LoxResult::RuntimeError {
+        LoxResult::RuntimeError {
             token: token.dup(),
             message: message.to_string(),
-        };
-        err.report("");
-        err
+        }
     }
     pub fn system_error(message: &str) -> LoxResult {
-        let err = LoxResult::SystemError {
+        LoxResult::SystemError {
             message: message.to_string(),
-        };
-        err.report("");
-        err
+        }
+    }
+    pub fn throw(token: &Token, value: LiteralValue) -> LoxResult {
+        LoxResult::Throw {
+            token: token.dup(),
+            value,
+        }
     }
 
-    fn report(&self, loc: &str) {
+    pub fn report(&self, loc: &str) {
         match self {
             LoxResult::ParseError { token, message } => {
                 if token.is(TokenType::EOF) {
@@ -88,7 +96,14 @@ impl LoxResult {
             LoxResult::SystemError { message } => {
                 eprintln!("System Error: {message}");
             }
-            LoxResult::Break | LoxResult::ReturnValue { .. } => {}
+            LoxResult::Throw { token, value } => {
+                eprintln!("Uncaught exception: {}\n[line {}]", value, token.line);
+            }
+            LoxResult::Break
+            | LoxResult::Continue
+            | LoxResult::NilChain
+            | LoxResult::ReturnValue { .. }
+            | LoxResult::Yield { .. } => {}
             
             LoxResult::Fail => {
                 panic!("should not get here")
@@ -1,16 +1,32 @@
 //use std::borrow::Borrow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::collections::HashMap;
 use std::ops::Deref;
+use std::path::Path;
+use std::path::PathBuf;
 
 use crate::lox_class::*;
+use crate::lox_instance::*;
 use crate::callable::*;
 use crate::entities::*;
 use crate::environment::*;
 use crate::errors::*;
 use crate::expr::*;
+use crate::lox_bigint::*;
+use crate::lox_decimal::*;
+use crate::lox_enum::*;
 use crate::lox_function::*;
+use crate::lox_generator::*;
+use crate::lox_iterator::*;
+use crate::lox_list::*;
+use crate::lox_map::*;
+use crate::lox_module::*;
+use crate::lox_trait::*;
+use crate::parser::*;
+use crate::pattern::*;
+use crate::resolver::*;
+use crate::scanner::*;
 use crate::stmt::*;
 use crate::native_functions::*;
 //use crate::lox_function::*;
@@ -18,49 +34,289 @@ use crate::native_functions::*;
 
 pub struct Interpreter {
    pub globals: Rc<RefCell<Environment>>,
+    error_class: Rc<LoxClass>,
     environment: RefCell<Rc<RefCell<Environment>>>,
     //nest: RefCell<usize>,
     locals: RefCell<HashMap<Rc<Expr>, usize>>,
+    // Files whose top-level code is running, outermost first. Imports are
+    // resolved relative to the last one.
+    loading: RefCell<Vec<PathBuf>>,
+    modules: RefCell<HashMap<PathBuf, Rc<LoxModule>>>,
+    // Frames of the generator being resumed, outermost last, and those of
+    // the generator being suspended, innermost first.
+    resuming: RefCell<Vec<Frame>>,
+    suspended: RefCell<Vec<Frame>>,
+    decimal_context: Cell<DecimalContext>,
+}
+
+fn module_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+// Integers taking part in decimal arithmetic.
+fn decimal_operand(value: &LiteralValue) -> Decimal {
+    match value {
+        LiteralValue::Int(n) => Decimal::from_bigint(BigInt::from_i64(*n)),
+        LiteralValue::BigInt(n) => Decimal::from_bigint(n.as_ref().clone()),
+        LiteralValue::Decimal(d) => d.as_ref().clone(),
+        _ => unreachable!("only integers and decimals are decimal operands"),
+    }
 }
 
 impl StmtVisitor<()> for Interpreter {
     fn visit_break_stmt(&self, _: Rc<Stmt>, _stmt: &BreakStmt) -> Result<(), LoxResult> {
         Err(LoxResult::Break)
     }
+    fn visit_continue_stmt(&self, _: Rc<Stmt>, _stmt: &ContinueStmt) -> Result<(), LoxResult> {
+        Err(LoxResult::Continue)
+    }
     fn visit_while_stmt(&self, _: Rc<Stmt>, stmt: &WhileStmt) -> Result<(), LoxResult> {
-        while self.is_truthy(&self.evaluate(stmt.condition.clone())?) {
+        // A resumed loop goes straight back into its body.
+        let mut resuming = match self.take_resume() {
+            Some(Frame::While) => true,
+            Some(_) => unreachable!("resumed into the wrong statement"),
+            None => false,
+        };
+        while resuming || self.is_truthy(&self.evaluate(stmt.condition.clone())?) {
+            resuming = false;
             match self.execute(stmt.body.clone()) {
                 Err(LoxResult::Break) => break,
+                Err(LoxResult::Continue) | Ok(_) => {}
+                Err(e @ LoxResult::Yield { .. }) => {
+                    self.suspend(Frame::While);
+                    return Err(e);
+                }
                 Err(e) => return Err(e),
-                Ok(_) => {}
+            }
+            if let Some(increment) = stmt.increment.clone() {
+                self.evaluate(increment)?;
             }
         }
 
         Ok(())
     }
+    // Each iteration gets a fresh environment for the loop variable, so
+    // closures created in the body capture that iteration's value.
+    fn visit_forin_stmt(&self, _: Rc<Stmt>, stmt: &ForInStmt) -> Result<(), LoxResult> {
+        let (mut iterator, mut resuming) = match self.take_resume() {
+            Some(Frame::ForIn(iterator)) => (iterator, true),
+            Some(_) => unreachable!("resumed into the wrong statement"),
+            None => {
+                let iterable = self.evaluate(stmt.iterable.clone())?;
+                (LoxIterator::new(self, &stmt.keyword, iterable)?, false)
+            }
+        };
+        let body = Rc::new(vec![stmt.body.clone()]);
+        loop {
+            // A resumed iteration gets its environment back from the block's
+            // own frame.
+            let mut environment = Environment::new_with_enclosing(self.environment.borrow().clone());
+            if !resuming {
+                match iterator.next(self, &stmt.keyword)? {
+                    Some(value) => environment.define(&stmt.name.as_string(), value),
+                    None => break,
+                }
+            }
+            resuming = false;
+            match self.execute_block(&body, environment) {
+                Err(LoxResult::Break) => break,
+                Err(LoxResult::Continue) | Ok(_) => {}
+                Err(e @ LoxResult::Yield { .. }) => {
+                    self.suspend(Frame::ForIn(iterator));
+                    return Err(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
     fn visit_expression_stmt(&self, _: Rc<Stmt>, stmt: &ExpressionStmt) -> Result<(), LoxResult> {
         self.evaluate(stmt.expression.clone())?;
         Ok(())
     }
     fn visit_print_stmt(&self, _: Rc<Stmt>, stmt: &PrintStmt) -> Result<(), LoxResult> {
         let value = self.evaluate(stmt.expression.clone())?;
-        println!("{value}");
+        println!("{}", self.stringify(&value)?);
+        Ok(())
+    }
+
+    fn visit_import_stmt(&self, _: Rc<Stmt>, stmt: &ImportStmt) -> Result<(), LoxResult> {
+        let module = self.import_module(&stmt.path)?;
+        if let Some(alias) = &stmt.alias {
+            self.environment
+                .borrow()
+                .borrow_mut()
+                .define(&alias.as_string(), LiteralValue::Module(Rc::clone(&module)));
+        }
+        for name in stmt.names.iter() {
+            let value = module.get(name)?;
+            self.environment
+                .borrow()
+                .borrow_mut()
+                .define(&name.as_string(), value);
+        }
         Ok(())
     }
 
+    fn visit_export_stmt(&self, _: Rc<Stmt>, stmt: &ExportStmt) -> Result<(), LoxResult> {
+        self.execute(stmt.declaration.clone())
+    }
+
+    fn visit_throw_stmt(&self, _: Rc<Stmt>, stmt: &ThrowStmt) -> Result<(), LoxResult> {
+        let value = self.evaluate(stmt.value.clone())?;
+        Err(LoxResult::throw(&stmt.keyword, value))
+    }
+
+    // Return, break and continue unwind as errors too, so the finally block
+    // runs for them as well. A finally block that itself unwinds replaces
+    // whatever was in flight.
+    // A yield suspends the try where it is, without running catch or
+    // finally, and resuming picks up in the same part.
+    fn visit_try_stmt(&self, _: Rc<Stmt>, stmt: &TryStmt) -> Result<(), LoxResult> {
+        let stage = match self.take_resume() {
+            Some(Frame::Try(stage)) => stage,
+            Some(_) => unreachable!("resumed into the wrong statement"),
+            None => TryStage::Body,
+        };
+        let result = match stage {
+            TryStage::Finally(pending) => return self.execute_finally(stmt, pending),
+            TryStage::Catch => {
+                let e = Environment::new_with_enclosing(self.environment.borrow().clone());
+                self.execute_block(stmt.catch_body.as_ref().unwrap(), e)
+            }
+            TryStage::Body => {
+                let e = Environment::new_with_enclosing(self.environment.borrow().clone());
+                let result = self.execute_block(&stmt.body, e);
+                if let Err(LoxResult::Yield { .. }) = result {
+                    self.suspend(Frame::Try(TryStage::Body));
+                    return result;
+                }
+
+                let caught = match result {
+                    Err(LoxResult::Throw { ref value, .. }) => Some(value.clone()),
+                    Err(LoxResult::RuntimeError { ref token, ref message }) => {
+                        Some(self.error_value(token, message))
+                    }
+                    _ => None,
+                };
+                match (&stmt.catch_name, &stmt.catch_body, caught) {
+                    (Some(name), Some(catch_body), Some(value)) => {
+                        let mut e = Environment::new_with_enclosing(self.environment.borrow().clone());
+                        e.define(&name.as_string(), value);
+                        self.execute_block(catch_body, e)
+                    }
+                    _ => result,
+                }
+            }
+        };
+        if let Err(LoxResult::Yield { .. }) = result {
+            self.suspend(Frame::Try(TryStage::Catch));
+            return result;
+        }
+
+        self.execute_finally(stmt, result)
+    }
+
     fn visit_if_stmt(&self, _: Rc<Stmt>, stmt: &IfStmt) -> Result<(), LoxResult> {
-        if self.is_truthy(&self.evaluate(stmt.condition.clone())?) {
+        let then_branch = match self.take_resume() {
+            Some(Frame::If { then_branch }) => then_branch,
+            Some(_) => unreachable!("resumed into the wrong statement"),
+            None => self.is_truthy(&self.evaluate(stmt.condition.clone())?),
+        };
+        let result = if then_branch {
             self.execute(stmt.then_branch.clone())
         } else if let Some(else_branch) = stmt.else_branch.clone() {
             self.execute(else_branch)
         } else {
             Ok(())
+        };
+        if let Err(LoxResult::Yield { .. }) = result {
+            self.suspend(Frame::If { then_branch });
         }
+        result
+    }
+
+    // Resuming at a yield carries on after it.
+    fn visit_yield_stmt(&self, _: Rc<Stmt>, stmt: &YieldStmt) -> Result<(), LoxResult> {
+        match self.take_resume() {
+            Some(Frame::Yield) => return Ok(()),
+            Some(_) => unreachable!("resumed into the wrong statement"),
+            None => {}
+        }
+        let value = if let Some(value) = stmt.value.clone() {
+            self.evaluate(value)?
+        } else {
+            LiteralValue::Nil
+        };
+        self.suspend(Frame::Yield);
+        Err(LoxResult::Yield { value })
     }
 
     fn visit_block_stmt(&self, _: Rc<Stmt>, stmt: &BlockStmt) -> Result<(), LoxResult> {
         let e = Environment::new_with_enclosing(self.environment.borrow().clone());
-        let _ = self.execute_block(&stmt.statements, e);
+        self.execute_block(&stmt.statements, e)
+    }
+
+    fn visit_destructure_stmt(&self, _: Rc<Stmt>, stmt: &DestructureStmt) -> Result<(), LoxResult> {
+        let value = self.evaluate(stmt.initializer.clone())?;
+        let values = if stmt.pattern.is(TokenType::LEFT_BRACKET) {
+            self.unpack_list(&stmt.pattern, value, stmt.names.len())?
+        } else {
+            stmt.names
+                .iter()
+                .map(|name| match &value {
+                    LiteralValue::Map(map) => map.get(&stmt.pattern, &LiteralValue::Str(name.as_string())),
+                    LiteralValue::Instance(_) => self.get_property(value.clone(), name),
+                    _ => Err(LoxResult::runtime_error(
+                        &stmt.pattern,
+                        &format!("Can only destructure fields of an instance or map, not '{value}'."),
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        for (name, value) in stmt.names.iter().zip(values) {
+            self.environment
+                .borrow()
+                .borrow_mut()
+                .define(&name.as_string(), value);
+        }
+        Ok(())
+    }
+
+    fn visit_multiassign_stmt(&self, _: Rc<Stmt>, stmt: &MultiAssignStmt) -> Result<(), LoxResult> {
+        let mut values = Vec::new();
+        for value in stmt.values.iter() {
+            values.push(self.evaluate(value.clone())?);
+        }
+        if values.len() != stmt.targets.len() {
+            let value = values.pop().unwrap();
+            values = self.unpack_list(&stmt.equals, value, stmt.targets.len())?;
+        }
+
+        // The resolver has already rejected local constants, so checking the
+        // global variables first means a failed statement assigns nothing.
+        for target in stmt.targets.iter() {
+            if let Expr::Variable(v) = target.deref() {
+                if !self.locals.borrow().contains_key(target) {
+                    self.environment.borrow().borrow().check_assign_global(&v.name)?;
+                }
+            }
+        }
+        for (target, value) in stmt.targets.iter().zip(values) {
+            self.assign_target(target, value)?;
+        }
+        Ok(())
+    }
+
+    fn visit_const_stmt(&self, _: Rc<Stmt>, stmt: &ConstStmt) -> Result<(), LoxResult> {
+        let value = self.evaluate(stmt.initializer.clone())?;
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .define_constant(&stmt.name.as_string(), value);
         Ok(())
     }
 
@@ -96,8 +352,41 @@ impl StmtVisitor<()> for Interpreter {
         }
     }
     
+    fn visit_trait_stmt(&self, _: Rc<Stmt>, stmt: &TraitStmt) -> Result<(), LoxResult> {
+        let required = stmt.required.iter().map(Token::as_string).collect();
+        let lox_trait = LoxTrait::new(
+            &stmt.name.as_string(),
+            &self.environment.borrow(),
+            Rc::clone(&stmt.methods),
+            required,
+        );
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .define(&stmt.name.as_string(), LiteralValue::Trait(Rc::new(lox_trait)));
+        Ok(())
+    }
+
+    fn visit_enum_stmt(&self, _: Rc<Stmt>, stmt: &EnumStmt) -> Result<(), LoxResult> {
+        let variants = stmt
+            .variants
+            .iter()
+            .zip(stmt.fields.iter())
+            .map(|(variant, fields)| {
+                let fields = fields.as_ref().map(|fields| fields.iter().map(Token::as_string).collect());
+                (variant.as_string(), fields)
+            })
+            .collect();
+        let lox_enum = LoxEnum::new(&stmt.name.as_string(), variants);
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .define(&stmt.name.as_string(), LiteralValue::Enum(Rc::new(lox_enum)));
+        Ok(())
+    }
+
     fn visit_class_stmt(&self, _: Rc<Stmt>, stmt: &ClassStmt) -> Result<(), LoxResult> {
-        let superclass = if let Some(superclass_expr) = &stmt.superclass {
+        let mut superclass = if let Some(superclass_expr) = &stmt.superclass {
             let superclass = self.evaluate(superclass_expr.clone())?;
 
             if let LiteralValue::Class(c) = superclass {
@@ -114,6 +403,27 @@ impl StmtVisitor<()> for Interpreter {
             None
         };
 
+        // `class A < B with T1, T2` is linearized as A, T2, T1, B: each trait
+        // is applied on top of what precedes it, so the class's own methods
+        // win over its traits', a later trait's over an earlier one's, and
+        // any trait's over the superclass's. `super` follows the same order.
+        let mut traits = Vec::new();
+        for used in stmt.traits.iter() {
+            match self.evaluate(used.clone())? {
+                LiteralValue::Trait(t) => {
+                    superclass = Some(t.apply(superclass));
+                    traits.push(t);
+                }
+                _ => {
+                    let name = match used.deref() {
+                        Expr::Variable(v) => &v.name,
+                        _ => &stmt.name,
+                    };
+                    return Err(LoxResult::runtime_error(name, "Can only use traits after 'with'."));
+                }
+            }
+        }
+
         self.environment
             .borrow()
             .borrow_mut()
@@ -142,20 +452,56 @@ impl StmtVisitor<()> for Interpreter {
             };
         }
 
-        let klass = LiteralValue::Class(Rc::new(LoxClass::new(
+        let static_methods = self.class_functions(&stmt.static_methods);
+        let getters = self.class_functions(&stmt.getters);
+        let setters = self.class_functions(&stmt.setters);
+
+        let klass = Rc::new(LoxClass::new(
             &stmt.name.as_string(), 
             superclass, 
-            methods
-        )));
+            methods,
+            static_methods,
+            getters,
+            setters,
+        ));
 
         if let Some(previous) = enclosing {
             self.environment.replace(previous);
         }
 
+        for used in &traits {
+            for method in used.required() {
+                if klass.find_method(method).is_none() {
+                    return Err(LoxResult::runtime_error(
+                        &stmt.name,
+                        &format!(
+                            "Class '{}' must implement '{}' required by trait '{}'.",
+                            stmt.name.as_string(),
+                            method,
+                            used.name()
+                        ),
+                    ));
+                }
+            }
+        }
+
         self.environment
             .borrow()
             .borrow_mut()
-            .assign(&stmt.name, klass)?;
+            .assign(&stmt.name, LiteralValue::Class(Rc::clone(&klass)))?;
+
+        // Static field initializers run once the class exists, so they can
+        // refer to the class and to static fields declared above them.
+        for field in stmt.static_fields.deref() {
+            if let Stmt::Var(var) = field.deref() {
+                let value = if let Some(initializer) = var.initializer.clone() {
+                    self.evaluate(initializer)?
+                } else {
+                    LiteralValue::Nil
+                };
+                klass.set(&var.name, value);
+            }
+        }
 
         Ok(())
     }
@@ -175,91 +521,36 @@ impl ExprVisitor<LiteralValue> for Interpreter {
     fn visit_binary_expr(&self, _: Rc<Expr>, expr: &BinaryExpr) -> Result<LiteralValue, LoxResult> {
         let left = self.evaluate(expr.left.clone())?;
         let right = self.evaluate(expr.right.clone())?;
-        let op = expr.operator.token_type();
-
-        let result = match (left, right) {
-            (LiteralValue::Num(left), LiteralValue::Num(right)) => match op {
-                TokenType::MINUS => LiteralValue::Num(left - right),
-                TokenType::SLASH => LiteralValue::Num(left / right),
-                TokenType::STAR => LiteralValue::Num(left * right),
-                TokenType::PLUS => LiteralValue::Num(left + right),
-                TokenType::GREATER => LiteralValue::Bool(left > right),
-                TokenType::GREATER_EQUAL => LiteralValue::Bool(left >= right),
-                TokenType::LESS => LiteralValue::Bool(left < right),
-                TokenType::LESS_EQUAL => LiteralValue::Bool(left <= right),
-                TokenType::BANG_EQUAL => LiteralValue::Bool(left != right),
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(left == right),
-                _ => {
-                    todo!();
-                }
-            },
-            (LiteralValue::Num(left), LiteralValue::Str(right)) => match op {
-                TokenType::PLUS => LiteralValue::Str(format!("{left}{right}")),
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
-                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
-                _ => LiteralValue::ArithmeticError,
-            },
-            (LiteralValue::Str(left), LiteralValue::Num(right)) => match op {
-                TokenType::PLUS => LiteralValue::Str(format!("{left}{right}")),
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
-                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
-                _ => LiteralValue::ArithmeticError,
-            },
-            (LiteralValue::Str(left), LiteralValue::Str(right)) => match op {
-                TokenType::PLUS => LiteralValue::Str(format!("{left}{right}")),
-                TokenType::BANG_EQUAL => LiteralValue::Bool(left != right),
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(left == right),
-                _ => LiteralValue::ArithmeticError,
-            },
-            (LiteralValue::Bool(left), LiteralValue::Bool(right)) => match op {
-                TokenType::BANG_EQUAL => LiteralValue::Bool(left != right),
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(left == right),
-                _ => LiteralValue::ArithmeticError,
-            },
-            (LiteralValue::Bool(_), LiteralValue::Str(_)) | (LiteralValue::Str(_), LiteralValue::Bool(_)) => match op {
-                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
-                _ => LiteralValue::NumsOrStringsError,
-            },
-            (LiteralValue::Nil, LiteralValue::Nil) => match op {
-                TokenType::BANG_EQUAL => LiteralValue::Bool(false),
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(true),
-                _ => LiteralValue::NumsOrStringsError,
-            },
-            (LiteralValue::Nil, _) | (_, LiteralValue::Nil) => match op {
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
-                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
-                _ => LiteralValue::NumsOrStringsError,
-            },
-            (LiteralValue::Func(a), LiteralValue::Func(b)) => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
-            (LiteralValue::Class(a), LiteralValue::Class(b)) => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
-            _ => match op {
-                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
-                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
-                TokenType::PLUS => LiteralValue::NumsOrStringsError,
-                _ => LiteralValue::ArithmeticError,
-            },
-        };
-
-
-            match result {
-                LiteralValue::ArithmeticError => Err(LoxResult::runtime_error(
-                &expr.operator,
-                "Operands must be numbers.",
-            )),
-            LiteralValue::NumsOrStringsError => Err(LoxResult::runtime_error(
-                &expr.operator,
-                "Operands must be two numbers or two strings.",
-            )),
-            _ => Ok(result),
-            
-        }
+        self.binary_op(&expr.operator, left, right)
     }
     fn visit_unary_expr(&self, _: Rc<Expr>, expr: &UnaryExpr) -> Result<LiteralValue, LoxResult> {
         let right = self.evaluate(expr.right.clone())?;
         match expr.operator.token_type() {
             TokenType::MINUS => match right {
-                LiteralValue::Num(n) => return Ok(LiteralValue::Num(-n)),
+                LiteralValue::Int(n) => Ok(match n.checked_neg() {
+                    Some(n) => LiteralValue::Int(n),
+                    None => LiteralValue::integer(-&BigInt::from_i64(n)),
+                }),
+                LiteralValue::BigInt(n) => Ok(LiteralValue::integer(-n.as_ref())),
+                LiteralValue::Num(n) => Ok(LiteralValue::Num(-n)),
+                LiteralValue::Decimal(d) => Ok(LiteralValue::Decimal(Rc::new(-d.as_ref()))),
+                LiteralValue::Instance(_) => self
+                    .call_special(&expr.operator, &right, "__neg__", Vec::new())?
+                    .ok_or_else(|| {
+                        LoxResult::runtime_error(&expr.operator, "Operand must be a number.")
+                    }),
+                _ => Err(LoxResult::runtime_error(
+                    &expr.operator,
+                    "Operand must be a number.",
+                )),
+            },
+            TokenType::TILDE => match right {
+                LiteralValue::Int(n) => Ok(LiteralValue::Int(!n)),
+                LiteralValue::Num(n) => Ok(LiteralValue::Int(!self.integral(&expr.operator, n)?)),
+                LiteralValue::BigInt(_) => Err(LoxResult::runtime_error(
+                    &expr.operator,
+                    "Bitwise operands must fit in 64 bits.",
+                )),
                 _ => Err(LoxResult::runtime_error(
                     &expr.operator,
                     "Operand must be a number.",
@@ -289,6 +580,10 @@ impl ExprVisitor<LiteralValue> for Interpreter {
             if self.is_truthy(&left) {
                 return Ok(left);
             }
+        } else if expr.operator.is(TokenType::QUESTION_QUESTION) {
+            if !matches!(left, LiteralValue::Nil) {
+                return Ok(left);
+            }
         } else if !self.is_truthy(&left) {
             return Ok(left);
         }
@@ -296,21 +591,80 @@ impl ExprVisitor<LiteralValue> for Interpreter {
         self.evaluate(expr.right.clone())
     }
 
-    fn visit_assign_expr(&self, wrapper: Rc<Expr>, expr: &AssignExpr) -> Result<LiteralValue, LoxResult> {
-        let value = self.evaluate(expr.value.clone())?;
-        if let Some(distance) = self.locals.borrow().get(&wrapper) {
-            self.environment.borrow().borrow_mut().assign_at(
-                *distance,
-                &expr.name,
-                value.clone(),
-            )?;
+    fn visit_match_expr(&self, _: Rc<Expr>, expr: &MatchExpr) -> Result<LiteralValue, LoxResult> {
+        let value = self.evaluate(expr.subject.clone())?;
+        for arm in expr.arms.iter() {
+            let mut bindings = Vec::new();
+            if self.match_pattern(&arm.pattern, &value, &mut bindings)? {
+                let mut environment = Environment::new_with_enclosing(self.environment.borrow().clone());
+                for (name, value) in bindings {
+                    environment.define(&name, value);
+                }
+                let previous = self.environment.replace(Rc::new(RefCell::new(environment)));
+                let result = match arm.body.deref() {
+                    Stmt::Expression(body) => self.evaluate(body.expression.clone()),
+                    _ => self.execute(arm.body.clone()).map(|_| LiteralValue::Nil),
+                };
+                self.environment.replace(previous);
+                return result;
+            }
+        }
+        Ok(LiteralValue::Nil)
+    }
+
+    fn visit_conditional_expr(&self, _: Rc<Expr>, expr: &ConditionalExpr) -> Result<LiteralValue, LoxResult> {
+        if self.is_truthy(&self.evaluate(expr.condition.clone())?) {
+            self.evaluate(expr.then_branch.clone())
         } else {
-            self.globals
-                .borrow_mut()
-                .assign(&expr.name, value.clone())?;
+            self.evaluate(expr.else_branch.clone())
         }
+    }
+
+    fn visit_optional_expr(&self, _: Rc<Expr>, expr: &OptionalExpr) -> Result<LiteralValue, LoxResult> {
+        match self.evaluate(expr.expression.clone()) {
+            Err(LoxResult::NilChain) => Ok(LiteralValue::Nil),
+            result => result,
+        }
+    }
+
+    fn visit_optionalget_expr(&self, _: Rc<Expr>, expr: &OptionalGetExpr) -> Result<LiteralValue, LoxResult> {
+        match self.evaluate(expr.literalvalue.clone())? {
+            LiteralValue::Nil => Err(LoxResult::NilChain),
+            literalvalue => self.get_property(literalvalue, &expr.name),
+        }
+    }
+
+    fn visit_assign_expr(&self, wrapper: Rc<Expr>, expr: &AssignExpr) -> Result<LiteralValue, LoxResult> {
+        let value = self.evaluate(expr.value.clone())?;
+        self.assign_variable(&expr.name, wrapper, value.clone())?;
+        Ok(value)
+    }
+
+    fn visit_compound_expr(&self, _: Rc<Expr>, expr: &CompoundExpr) -> Result<LiteralValue, LoxResult> {
+        let (_, value) = self.update_target(&expr.target, &expr.operator, |current| {
+            let operand = self.evaluate(expr.value.clone())?;
+            self.binary_op(&expr.operator, current, operand)
+        })?;
         Ok(value)
     }
+
+    fn visit_update_expr(&self, _: Rc<Expr>, expr: &UpdateExpr) -> Result<LiteralValue, LoxResult> {
+        let (old, new) = self.update_target(&expr.target, &expr.operator, |current| {
+            if let LiteralValue::Int(_)
+            | LiteralValue::BigInt(_)
+            | LiteralValue::Num(_)
+            | LiteralValue::Decimal(_) = current
+            {
+                self.binary_op(&expr.operator, current, LiteralValue::Int(1))
+            } else {
+                Err(LoxResult::runtime_error(
+                    &expr.operator,
+                    "Operand must be a number.",
+                ))
+            }
+        })?;
+        Ok(if expr.prefix { new } else { old })
+    }
     
     fn visit_call_expr(&self, _: Rc<Expr>, expr: &CallExpr) -> Result<LiteralValue, LoxResult> {
         let callee = self.evaluate(expr.callee.clone())?;
@@ -320,9 +674,21 @@ impl ExprVisitor<LiteralValue> for Interpreter {
             arguments.push(self.evaluate(argument)?);
         }
 
+        let mut named: Vec<(Token, LiteralValue)> = Vec::new();
+        for (name, argument) in expr.names.iter().zip(expr.named.iter()) {
+            if named.iter().any(|(other, _)| other.as_string() == name.as_string()) {
+                return Err(LoxResult::runtime_error(
+                    name,
+                    &format!("Duplicate named argument '{}'.", name.as_string()),
+                ));
+            }
+            named.push((name.dup(), self.evaluate(argument.clone())?));
+        }
+
         let (callfunc, klass): (Option<Rc<dyn LoxCallable>>, Option<Rc<LoxClass>>) = match callee {
             LiteralValue::Func(f) => (Some(f), None),
             LiteralValue::Native(n) => (Some(n.func.clone()), None),
+            LiteralValue::Variant(v) => (Some(v.constructor()), None),
             LiteralValue::Class(c) => {
                 let klass = Rc::clone(&c);
                 (Some(c), Some(klass))
@@ -330,7 +696,7 @@ impl ExprVisitor<LiteralValue> for Interpreter {
             _ => (None, None),
         };
         if let Some(callfunc) = callfunc {
-            if arguments.len() != callfunc.arity() {
+            if named.is_empty() && !callfunc.arity().accepts(arguments.len()) {
                 return Err(LoxResult::runtime_error(
                     &expr.paren,
                     &format!(
@@ -340,7 +706,19 @@ impl ExprVisitor<LiteralValue> for Interpreter {
                     ),
                 ));
             }
-            callfunc.call(self, arguments, klass)
+            // Native functions have no token of their own, so their errors
+            // are pinned to the call site.
+            let result = if named.is_empty() {
+                callfunc.call(self, arguments, klass)
+            } else {
+                callfunc.call_named(self, arguments, named, klass)
+            };
+            result.map_err(|e| match e {
+                LoxResult::SystemError { message } => {
+                    LoxResult::runtime_error(&expr.paren, &message)
+                }
+                e => e,
+            })
         } else {
             Err(LoxResult::runtime_error(
                 &expr.paren,
@@ -351,18 +729,14 @@ impl ExprVisitor<LiteralValue> for Interpreter {
     
     fn visit_get_expr(&self, _: Rc<Expr>, expr: &GetExpr) -> Result<LiteralValue, LoxResult> {
         let literalvalue = self.evaluate(expr.literalvalue.clone())?;
-        if let LiteralValue::Instance(inst) = literalvalue {
-             Ok(inst.get(&expr.name, &inst)?)
-        } else {
-            Err(LoxResult::runtime_error(&expr.name, "Only instances have properties."))
-        }
+        self.get_property(literalvalue, &expr.name)
     }
     
     fn visit_set_expr(&self, _: Rc<Expr>, expr: &SetExpr) -> Result<LiteralValue, LoxResult> {
         let literalvalue = self.evaluate(expr.literalvalue.clone())?;
-        if let LiteralValue::Instance(inst) = literalvalue {
+        if let LiteralValue::Instance(_) | LiteralValue::Class(_) = literalvalue {
             let value = self.evaluate(expr.value.clone())?;
-            inst.set(&expr.name, value.clone());
+            self.set_property(literalvalue, &expr.name, value.clone())?;
             Ok(value)
         } else {
             Err(LoxResult::runtime_error(
@@ -372,15 +746,85 @@ impl ExprVisitor<LiteralValue> for Interpreter {
         }
     }
     
+    fn visit_lambda_expr(&self, _: Rc<Expr>, expr: &LambdaExpr) -> Result<LiteralValue, LoxResult> {
+        let function = LoxFunction::new(&expr.declaration, self.environment.borrow().deref(), false);
+        Ok(LiteralValue::Func(Rc::new(function)))
+    }
+
+    fn visit_list_expr(&self, _: Rc<Expr>, expr: &ListExpr) -> Result<LiteralValue, LoxResult> {
+        let mut elements = Vec::new();
+        for element in expr.elements.clone() {
+            elements.push(self.evaluate(element)?);
+        }
+        Ok(LiteralValue::List(Rc::new(LoxList::new(elements))))
+    }
+
+    fn visit_map_expr(&self, _: Rc<Expr>, expr: &MapExpr) -> Result<LiteralValue, LoxResult> {
+        let map = LoxMap::new();
+        for (key, value) in expr.keys.iter().zip(expr.values.iter()) {
+            let key = self.evaluate(key.clone())?;
+            let key = MapKey::checked(&expr.brace, &key)?;
+            map.insert(key, self.evaluate(value.clone())?);
+        }
+        Ok(LiteralValue::Map(Rc::new(map)))
+    }
+
+    fn visit_index_expr(&self, _: Rc<Expr>, expr: &IndexExpr) -> Result<LiteralValue, LoxResult> {
+        let literalvalue = self.evaluate(expr.literalvalue.clone())?;
+        let index = self.evaluate(expr.index.clone())?;
+        match literalvalue {
+            LiteralValue::List(list) => list.get(&expr.bracket, &index),
+            LiteralValue::Map(map) => map.get(&expr.bracket, &index),
+            LiteralValue::Instance(_) => self
+                .call_special(&expr.bracket, &literalvalue, "__index__", vec![index])?
+                .ok_or_else(|| {
+                    LoxResult::runtime_error(&expr.bracket, "Only lists and maps can be indexed.")
+                }),
+            _ => Err(LoxResult::runtime_error(
+                &expr.bracket,
+                "Only lists and maps can be indexed.",
+            )),
+        }
+    }
+
+    fn visit_indexset_expr(&self, _: Rc<Expr>, expr: &IndexSetExpr) -> Result<LiteralValue, LoxResult> {
+        let literalvalue = self.evaluate(expr.literalvalue.clone())?;
+        let index = self.evaluate(expr.index.clone())?;
+        match literalvalue {
+            LiteralValue::List(list) => {
+                let value = self.evaluate(expr.value.clone())?;
+                list.set(&expr.bracket, &index, value.clone())?;
+                Ok(value)
+            }
+            LiteralValue::Map(map) => {
+                let value = self.evaluate(expr.value.clone())?;
+                map.set(&expr.bracket, &index, value.clone())?;
+                Ok(value)
+            }
+            _ => Err(LoxResult::runtime_error(
+                &expr.bracket,
+                "Only lists and maps can be indexed.",
+            )),
+        }
+    }
+
+    fn visit_stringify_expr(&self, _: Rc<Expr>, expr: &StringifyExpr) -> Result<LiteralValue, LoxResult> {
+        let value = self.evaluate(expr.expression.clone())?;
+        Ok(LiteralValue::Str(self.stringify(&value)?))
+    }
+
     fn visit_super_expr(&self, wrapper: Rc<Expr>, expr: &SuperExpr) -> Result<LiteralValue, LoxResult> {
         let distance = *self.locals.borrow().get(&wrapper).unwrap();
-        let superclass = if let Ok(LiteralValue::Class(superclass)) =
-            self.environment.borrow().borrow().get_at(distance, "super")
-            {
-                superclass
-            } else {
-                panic!();
-            };
+        // A trait used by a class with nothing beneath it has no super.
+        let superclass = match self.environment.borrow().borrow().get_at(distance, "super") {
+            Ok(LiteralValue::Class(superclass)) => superclass,
+            _ => {
+                return Err(LoxResult::runtime_error(
+                    &expr.method,
+                    &format!("Undefined property '{}'.", expr.method.as_string()),
+                ))
+            }
+        };
 
         let literalvalue = self 
             .environment
@@ -390,7 +834,15 @@ impl ExprVisitor<LiteralValue> for Interpreter {
             .ok()
             .unwrap();
 
-        if let Some(method) = superclass.find_method(&expr.method.as_string()) {
+        // Inside a static method `this` is the class, so `super` reaches the
+        // superclass's static methods instead of its instance methods.
+        let method = if let LiteralValue::Class(_) = literalvalue {
+            superclass.find_static_method(&expr.method.as_string())
+        } else {
+            superclass.find_method(&expr.method.as_string())
+        };
+
+        if let Some(method) = method {
             if let LiteralValue::Func(func) = method {
                 Ok(func.bind(&literalvalue))
             } else {
@@ -409,20 +861,206 @@ impl ExprVisitor<LiteralValue> for Interpreter {
 
 impl Interpreter {
     pub fn new() -> Interpreter {
-        let globals = Rc::new(RefCell::new(Environment::new()));
+        let globals = Self::global_environment();
 
-        globals.borrow_mut().define(
-            "clock",
+        Interpreter {
+            globals: Rc::clone(&globals), 
+            error_class: Rc::new(LoxClass::new(
+                "Error",
+                None,
+                HashMap::new(),
+                HashMap::new(),
+                HashMap::new(),
+                HashMap::new(),
+            )),
+            environment: RefCell::new(Rc::clone(&globals)),
+            locals: RefCell::new(HashMap::new()),
+            loading: RefCell::new(Vec::new()),
+            modules: RefCell::new(HashMap::new()),
+            resuming: RefCell::new(Vec::new()),
+            suspended: RefCell::new(Vec::new()),
+            decimal_context: Cell::new(DecimalContext::default()),
+        }
+    }
+
+    // Every file, the main script and each module, runs in its own global
+    // environment holding the native functions. They are constants so that a
+    // script can shadow them with a declaration but not reassign them.
+    fn global_environment() -> Rc<RefCell<Environment>> {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+
+        globals.borrow_mut().define_constant(
+            "clock",
             LiteralValue::Native(Rc::new(LoxNative {
                 func: Rc::new(NativeClock {}),
             })),
         );
+        globals.borrow_mut().define_constant(
+            "len",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeLen {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "append",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeAppend {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "keys",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeKeys {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "has",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeHas {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "str",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeStr {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "range",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeRange {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "int",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeInt {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "float",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeFloat {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "Decimal",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeDecimal {}),
+            })),
+        );
+        globals.borrow_mut().define_constant(
+            "decimalContext",
+            LiteralValue::Native(Rc::new(LoxNative {
+                func: Rc::new(NativeDecimalContext {}),
+            })),
+        );
+        globals
+    }
 
-        Interpreter {
-            globals: Rc::clone(&globals), 
-            environment: RefCell::new(Rc::clone(&globals)),
-            locals: RefCell::new(HashMap::new()),
+    // Imports in the main script are resolved relative to its directory.
+    pub fn set_main_file(&self, path: &str) {
+        let path = Path::new(path);
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.loading.borrow_mut().push(path);
+    }
+
+    fn import_module(&self, path_token: &Token) -> Result<Rc<LoxModule>, LoxResult> {
+        let relative = path_token.literal.clone().unwrap().to_string();
+        let base = self
+            .loading
+            .borrow()
+            .last()
+            .and_then(|file| file.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        let path = base.join(&relative).canonicalize().map_err(|_| {
+            LoxResult::runtime_error(path_token, &format!("Could not find module '{relative}'."))
+        })?;
+
+        if let Some(module) = self.modules.borrow().get(&path) {
+            return Ok(Rc::clone(module));
+        }
+
+        if let Some(start) = self.loading.borrow().iter().position(|file| *file == path) {
+            let chain: Vec<String> = self.loading.borrow()[start..]
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|file| module_name(file))
+                .collect();
+            return Err(LoxResult::runtime_error(
+                path_token,
+                &format!("Import cycle: {}.", chain.join(" -> ")),
+            ));
         }
+
+        let source = std::fs::read_to_string(&path).map_err(|_| {
+            LoxResult::runtime_error(path_token, &format!("Could not read module '{relative}'."))
+        })?;
+
+        self.loading.borrow_mut().push(path.clone());
+        let result = self.load_module(&path, source, path_token);
+        self.loading.borrow_mut().pop();
+
+        let module = Rc::new(result?);
+        self.modules.borrow_mut().insert(path, Rc::clone(&module));
+        Ok(module)
+    }
+
+    // Runs a module's code once in a fresh global environment. Scan, parse
+    // and resolve errors have already been reported by the time this
+    // fails, so the import itself only reports that loading failed.
+    fn load_module(&self, path: &Path, source: String, path_token: &Token) -> Result<LoxModule, LoxResult> {
+        let name = module_name(path);
+        let failed = || LoxResult::runtime_error(path_token, &format!("Could not load module '{name}'."));
+
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan().map_err(|_| failed())?;
+        let mut parser = Parser::new(tokens);
+        let statements = Rc::new(parser.parse().map_err(|_| failed())?);
+
+        let resolver = Resolver::new(self);
+        resolver.resolve(&statements).map_err(|_| failed())?;
+        if !resolver.success() {
+            return Err(failed());
+        }
+
+        let globals = Self::global_environment();
+        let previous = self.environment.replace(Rc::clone(&globals));
+        let result = self.interpret(&statements);
+        self.environment.replace(previous);
+        result?;
+
+        let exports = statements
+            .iter()
+            .flat_map(|statement| match statement.deref() {
+                Stmt::Export(export) => match export.declaration.deref() {
+                    Stmt::Var(var) => vec![var.name.as_string()],
+                    Stmt::Const(constant) => vec![constant.name.as_string()],
+                    Stmt::Destructure(d) => d.names.iter().map(Token::as_string).collect(),
+                    Stmt::Function(function) => vec![function.name.as_string()],
+                    Stmt::Class(class) => vec![class.name.as_string()],
+                    Stmt::Enum(e) => vec![e.name.as_string()],
+                    Stmt::Trait(t) => vec![t.name.as_string()],
+                    _ => Vec::new(),
+                },
+                _ => Vec::new(),
+            })
+            .collect();
+
+        Ok(LoxModule::new(&name, globals, exports))
+    }
+
+    // Evaluates an expression somewhere other than the current environment,
+    // as for a parameter default, which belongs to the function's closure.
+    pub fn evaluate_in(
+        &self,
+        expr: Rc<Expr>,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        let previous = self.environment.replace(Rc::clone(environment));
+        let result = self.evaluate(expr);
+        self.environment.replace(previous);
+        result
     }
 
     fn evaluate(&self, expr: Rc<Expr>) -> Result<LiteralValue, LoxResult> {
@@ -438,18 +1076,931 @@ impl Interpreter {
         statements: &Rc<Vec<Rc<Stmt>>>,
         environment: Environment,
     ) -> Result<(), LoxResult> {
-        let previous = self.environment.replace(Rc::new(RefCell::new(environment)));
-        let result = statements
-            .iter()
-            .try_for_each(|statement| self.execute(statement.clone()));
+        // A block being resumed ignores the new environment and carries on
+        // in its old one from the statement that yielded.
+        let (environment, start) = match self.take_resume() {
+            Some(Frame::Block { environment, index }) => (environment, index),
+            Some(_) => unreachable!("resumed into the wrong statement"),
+            None => (Rc::new(RefCell::new(environment)), 0),
+        };
+        let previous = self.environment.replace(Rc::clone(&environment));
+        let mut result = Ok(());
+        for (index, statement) in statements.iter().enumerate().skip(start) {
+            result = self.execute(statement.clone());
+            if let Err(LoxResult::Yield { .. }) = result {
+                self.suspend(Frame::Block { environment, index });
+                break;
+            }
+            if result.is_err() {
+                break;
+            }
+        }
 
         self.environment.replace(previous);
 
         result
     }
 
+    fn execute_finally(&self, stmt: &TryStmt, pending: Result<(), LoxResult>) -> Result<(), LoxResult> {
+        if let Some(finally_body) = &stmt.finally_body {
+            let e = Environment::new_with_enclosing(self.environment.borrow().clone());
+            let result = self.execute_block(finally_body, e);
+            if let Err(LoxResult::Yield { .. }) = result {
+                self.suspend(Frame::Try(TryStage::Finally(pending)));
+                return result;
+            }
+            result?;
+        }
+        pending
+    }
+
+    // Runs a generator's body until it yields, giving the value and the
+    // frames to resume from, or until it finishes. Generators can run
+    // inside other generators, so the outer one's frames are set aside.
+    pub fn resume_generator(
+        &self,
+        body: &Rc<Vec<Rc<Stmt>>>,
+        environment: Environment,
+        frames: Vec<Frame>,
+    ) -> Result<Option<(LiteralValue, Vec<Frame>)>, LoxResult> {
+        let outer_resuming = self.resuming.replace(frames);
+        let outer_suspended = self.suspended.take();
+        let result = self.execute_block(body, environment);
+        let frames = self.suspended.replace(outer_suspended);
+        self.resuming.replace(outer_resuming);
+
+        match result {
+            Err(LoxResult::Yield { value }) => Ok(Some((value, frames))),
+            Ok(()) | Err(LoxResult::ReturnValue { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn take_resume(&self) -> Option<Frame> {
+        self.resuming.borrow_mut().pop()
+    }
+
+    fn suspend(&self, frame: Frame) {
+        self.suspended.borrow_mut().push(frame);
+    }
+
+    // Functions declared in a class body other than its instance methods.
+    // None of them can be an initializer.
+    fn class_functions(&self, declarations: &[Rc<Stmt>]) -> HashMap<String, LiteralValue> {
+        let mut functions = HashMap::new();
+        for declaration in declarations {
+            if let Stmt::Function(func) = declaration.deref() {
+                let function = LiteralValue::Func(Rc::new(LoxFunction::new(
+                    func,
+                    &self.environment.borrow(),
+                    false,
+                )));
+                functions.insert(func.name.as_string(), function);
+            } else {
+                panic!("non-function method in class");
+            }
+        }
+        functions
+    }
+
+    fn unpack_list(&self, token: &Token, value: LiteralValue, count: usize) -> Result<Vec<LiteralValue>, LoxResult> {
+        match value {
+            LiteralValue::List(list) if list.len() == count => Ok(list.elements.borrow().clone()),
+            LiteralValue::List(list) => Err(LoxResult::runtime_error(
+                token,
+                &format!("Expected a list of {count} elements but got {}.", list.len()),
+            )),
+            _ => Err(LoxResult::runtime_error(
+                token,
+                &format!("Can only unpack a list, not '{value}'."),
+            )),
+        }
+    }
+
+    fn assign_target(&self, target: &Rc<Expr>, value: LiteralValue) -> Result<(), LoxResult> {
+        match target.deref() {
+            Expr::Variable(v) => self.assign_variable(&v.name, target.clone(), value),
+            Expr::Get(get) => {
+                let literalvalue = self.evaluate(get.literalvalue.clone())?;
+                self.set_property(literalvalue, &get.name, value)
+            }
+            Expr::Index(index) => {
+                let literalvalue = self.evaluate(index.literalvalue.clone())?;
+                let key = self.evaluate(index.index.clone())?;
+                match literalvalue {
+                    LiteralValue::List(list) => list.set(&index.bracket, &key, value),
+                    LiteralValue::Map(map) => map.set(&index.bracket, &key, value),
+                    _ => Err(LoxResult::runtime_error(
+                        &index.bracket,
+                        "Only lists and maps can be indexed.",
+                    )),
+                }
+            }
+            _ => panic!("parser only allows assignable targets"),
+        }
+    }
+
+    fn get_property(&self, literalvalue: LiteralValue, name: &Token) -> Result<LiteralValue, LoxResult> {
+        match literalvalue {
+            LiteralValue::Instance(inst) => inst.get(name, &inst, self),
+            LiteralValue::Class(klass) => klass.get(name, &klass),
+            LiteralValue::Module(module) => module.get(name),
+            LiteralValue::Generator(generator) => generator.get(name, &generator),
+            LiteralValue::Decimal(decimal) => decimal_property(&decimal, name),
+            LiteralValue::Enum(e) => e.get(name),
+            LiteralValue::EnumValue(value) => value.get(name),
+            _ => Err(LoxResult::runtime_error(name, "Only instances have properties.")),
+        }
+    }
+
+    fn set_property(&self, literalvalue: LiteralValue, name: &Token, value: LiteralValue) -> Result<(), LoxResult> {
+        match literalvalue {
+            LiteralValue::Instance(inst) => inst.assign(name, value, &inst, self),
+            LiteralValue::Class(klass) => {
+                klass.set(name, value);
+                Ok(())
+            }
+            _ => Err(LoxResult::runtime_error(name, "Only instances have fields.")),
+        }
+    }
+
+    fn assign_variable(&self, name: &Token, expr: Rc<Expr>, value: LiteralValue) -> Result<(), LoxResult> {
+        if let Some(distance) = self.locals.borrow().get(&expr) {
+            self.environment
+                .borrow()
+                .borrow_mut()
+                .assign_at(*distance, name, value)
+        } else {
+            self.environment.borrow().borrow_mut().assign_global(name, value)
+        }
+    }
+
+    // Reads the current value of an assignment target, computes its new value
+    // and stores it back, evaluating the target's object and index only once.
+    // Returns the old and new values.
+    fn update_target<F>(
+        &self,
+        target: &Rc<Expr>,
+        operator: &Token,
+        compute: F,
+    ) -> Result<(LiteralValue, LiteralValue), LoxResult>
+    where
+        F: FnOnce(LiteralValue) -> Result<LiteralValue, LoxResult>,
+    {
+        match target.deref() {
+            Expr::Variable(v) => {
+                let old = self.look_up_variable(&v.name, target.clone())?;
+                let new = compute(old.clone())?;
+                self.assign_variable(&v.name, target.clone(), new.clone())?;
+                Ok((old, new))
+            }
+            Expr::Get(get) => {
+                let literalvalue = self.evaluate(get.literalvalue.clone())?;
+                let old = self.get_property(literalvalue.clone(), &get.name)?;
+                let new = compute(old.clone())?;
+                self.set_property(literalvalue, &get.name, new.clone())?;
+                Ok((old, new))
+            }
+            Expr::Index(index) => {
+                let literalvalue = self.evaluate(index.literalvalue.clone())?;
+                let key = self.evaluate(index.index.clone())?;
+                match literalvalue {
+                    LiteralValue::List(list) => {
+                        let old = list.get(&index.bracket, &key)?;
+                        let new = compute(old.clone())?;
+                        list.set(&index.bracket, &key, new.clone())?;
+                        Ok((old, new))
+                    }
+                    LiteralValue::Map(map) => {
+                        let old = map.get(&index.bracket, &key)?;
+                        let new = compute(old.clone())?;
+                        map.set(&index.bracket, &key, new.clone())?;
+                        Ok((old, new))
+                    }
+                    _ => Err(LoxResult::runtime_error(
+                        &index.bracket,
+                        "Only lists and maps can be indexed.",
+                    )),
+                }
+            }
+            _ => Err(LoxResult::runtime_error(operator, "Invalid assignment target.")),
+        }
+    }
+
+    // Shared by binary expressions and compound assignment, which applies
+    // the same operator to a target's current value.
+    fn binary_op(
+        &self,
+        operator: &Token,
+        left: LiteralValue,
+        right: LiteralValue,
+    ) -> Result<LiteralValue, LoxResult> {
+        let op = operator.token_type();
+
+        if matches!(left, LiteralValue::Instance(_)) || matches!(right, LiteralValue::Instance(_)) {
+            if let Some(result) = self.overloaded_binary(operator, &left, &right)? {
+                return Ok(result);
+            }
+            // Without __add__, an instance added to a string is concatenated
+            // using its string conversion.
+            if let (LiteralValue::Str(_), LiteralValue::Instance(_))
+            | (LiteralValue::Instance(_), LiteralValue::Str(_)) = (&left, &right)
+            {
+                if operator.is(TokenType::PLUS) {
+                    return Ok(LiteralValue::Str(format!(
+                        "{}{}",
+                        self.stringify(&left)?,
+                        self.stringify(&right)?
+                    )));
+                }
+            }
+        }
+
+        let result = match (left, right) {
+            (LiteralValue::Int(left), LiteralValue::Int(right)) => self.int_op(operator, left, right)?,
+            (LiteralValue::Int(left), LiteralValue::Num(right)) => self.float_op(operator, left as f64, right)?,
+            (LiteralValue::Num(left), LiteralValue::Int(right)) => self.float_op(operator, left, right as f64)?,
+            (LiteralValue::Num(left), LiteralValue::Num(right)) => self.float_op(operator, left, right)?,
+            (LiteralValue::BigInt(left), LiteralValue::BigInt(right)) => self.big_op(operator, &left, &right)?,
+            (LiteralValue::BigInt(left), LiteralValue::Int(right)) => {
+                self.big_op(operator, &left, &BigInt::from_i64(right))?
+            }
+            (LiteralValue::Int(left), LiteralValue::BigInt(right)) => {
+                self.big_op(operator, &BigInt::from_i64(left), &right)?
+            }
+            (LiteralValue::BigInt(left), LiteralValue::Num(right)) => self.float_op(operator, left.to_f64(), right)?,
+            (LiteralValue::Num(left), LiteralValue::BigInt(right)) => self.float_op(operator, left, right.to_f64())?,
+            (LiteralValue::Decimal(left), right @ (LiteralValue::Int(_) | LiteralValue::BigInt(_) | LiteralValue::Decimal(_))) => {
+                self.decimal_op(operator, &left, &decimal_operand(&right))?
+            }
+            (left @ (LiteralValue::Int(_) | LiteralValue::BigInt(_)), LiteralValue::Decimal(right)) => {
+                self.decimal_op(operator, &decimal_operand(&left), &right)?
+            }
+            // Decimals exist to avoid binary rounding, so they don't mix with
+            // floats. Only whole numbers, which both hold exactly, are equal,
+            // just as they share a map key.
+            (left @ LiteralValue::Decimal(_), right @ LiteralValue::Num(_))
+            | (left @ LiteralValue::Num(_), right @ LiteralValue::Decimal(_)) => {
+                let equal = MapKey::from_value(&left) == MapKey::from_value(&right);
+                match op {
+                    TokenType::EQUAL_EQUAL => LiteralValue::Bool(equal),
+                    TokenType::BANG_EQUAL => LiteralValue::Bool(!equal),
+                    _ => {
+                        return Err(LoxResult::runtime_error(
+                            operator,
+                            "Can't mix decimals and floats.",
+                        ))
+                    }
+                }
+            }
+            (left @ (LiteralValue::BigInt(_) | LiteralValue::Decimal(_)), LiteralValue::Str(right)) => match op {
+                TokenType::PLUS => LiteralValue::Str(format!("{left}{right}")),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Str(left), right @ (LiteralValue::BigInt(_) | LiteralValue::Decimal(_))) => match op {
+                TokenType::PLUS => LiteralValue::Str(format!("{left}{right}")),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Int(left), LiteralValue::Str(right)) => match op {
+                TokenType::PLUS => LiteralValue::Str(format!("{left}{right}")),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Str(left), LiteralValue::Int(right)) => match op {
+                TokenType::PLUS => LiteralValue::Str(format!("{left}{right}")),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Num(left), LiteralValue::Str(right)) => match op {
+                TokenType::PLUS => LiteralValue::Str(format!("{}{right}", LiteralValue::Num(left))),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Str(left), LiteralValue::Num(right)) => match op {
+                TokenType::PLUS => LiteralValue::Str(format!("{left}{}", LiteralValue::Num(right))),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Str(left), LiteralValue::Str(right)) => match op {
+                TokenType::PLUS => LiteralValue::Str(format!("{left}{right}")),
+                TokenType::BANG_EQUAL => LiteralValue::Bool(left != right),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(left == right),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Bool(left), LiteralValue::Bool(right)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(left != right),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(left == right),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Bool(_), LiteralValue::Str(_)) | (LiteralValue::Str(_), LiteralValue::Bool(_)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                _ => LiteralValue::NumsOrStringsError,
+            },
+            (LiteralValue::Nil, LiteralValue::Nil) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(false),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(true),
+                _ => LiteralValue::NumsOrStringsError,
+            },
+            (LiteralValue::Nil, _) | (_, LiteralValue::Nil) => match op {
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                _ => LiteralValue::NumsOrStringsError,
+            },
+            (LiteralValue::Func(a), LiteralValue::Func(b)) => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+            (LiteralValue::Class(a), LiteralValue::Class(b)) => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+            (LiteralValue::Trait(a), LiteralValue::Trait(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Module(a), LiteralValue::Module(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Instance(a), LiteralValue::Instance(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::List(a), LiteralValue::List(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Map(a), LiteralValue::Map(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Generator(a), LiteralValue::Generator(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Enum(a), LiteralValue::Enum(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Variant(a), LiteralValue::Variant(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            // Enum values are equal when they are the same variant with equal
+            // fields.
+            (LiteralValue::EnumValue(a), LiteralValue::EnumValue(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!self.enum_values_equal(operator, &a, &b)?),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(self.enum_values_equal(operator, &a, &b)?),
+                _ => LiteralValue::ArithmeticError,
+            },
+            (LiteralValue::Range(a), LiteralValue::Range(b)) => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(!Rc::ptr_eq(&a, &b)),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(Rc::ptr_eq(&a, &b)),
+                _ => LiteralValue::ArithmeticError,
+            },
+            _ => match op {
+                TokenType::BANG_EQUAL => LiteralValue::Bool(true),
+                TokenType::EQUAL_EQUAL => LiteralValue::Bool(false),
+                TokenType::PLUS => LiteralValue::NumsOrStringsError,
+                _ => LiteralValue::ArithmeticError,
+            },
+        };
+
+
+            match result {
+                LiteralValue::ArithmeticError => Err(LoxResult::runtime_error(
+                operator,
+                "Operands must be numbers.",
+            )),
+            LiteralValue::NumsOrStringsError => Err(LoxResult::runtime_error(
+                operator,
+                "Operands must be two numbers or two strings.",
+            )),
+            _ => Ok(result),
+            
+        }
+    }
+
+    // Classes overload operators by defining special methods, which are
+    // looked up on the left operand. `__eq__` may come from either side, and
+    // the comparisons other than `<` fall back to `__lt__` when a class does
+    // not define them. Returns None when no special method applies.
+    fn overloaded_binary(
+        &self,
+        operator: &Token,
+        left: &LiteralValue,
+        right: &LiteralValue,
+    ) -> Result<Option<LiteralValue>, LoxResult> {
+        let special = |receiver: &LiteralValue, name: &str, argument: &LiteralValue| {
+            self.call_special(operator, receiver, name, vec![argument.clone()])
+        };
+        let negated = |result: Option<LiteralValue>| {
+            result.map(|value| LiteralValue::Bool(!self.is_truthy(&value)))
+        };
+
+        let result = match operator.token_type() {
+            TokenType::EQUAL_EQUAL | TokenType::BANG_EQUAL => {
+                let equal = match special(left, "__eq__", right)? {
+                    Some(value) => Some(value),
+                    None => special(right, "__eq__", left)?,
+                };
+                if operator.is(TokenType::BANG_EQUAL) {
+                    negated(equal)
+                } else {
+                    equal.map(|value| LiteralValue::Bool(self.is_truthy(&value)))
+                }
+            }
+            TokenType::LESS => special(left, "__lt__", right)?,
+            TokenType::GREATER => match special(left, "__gt__", right)? {
+                Some(value) => Some(value),
+                None => special(right, "__lt__", left)?,
+            },
+            TokenType::LESS_EQUAL => match special(left, "__le__", right)? {
+                Some(value) => Some(value),
+                None => negated(special(right, "__lt__", left)?),
+            },
+            TokenType::GREATER_EQUAL => match special(left, "__ge__", right)? {
+                Some(value) => Some(value),
+                None => negated(special(left, "__lt__", right)?),
+            },
+            TokenType::PLUS => special(left, "__add__", right)?,
+            TokenType::MINUS => special(left, "__sub__", right)?,
+            TokenType::STAR => special(left, "__mul__", right)?,
+            TokenType::SLASH => special(left, "__div__", right)?,
+            TokenType::PERCENT => special(left, "__mod__", right)?,
+            TokenType::STAR_STAR => special(left, "__pow__", right)?,
+            _ => None,
+        };
+        Ok(result)
+    }
+
+    pub fn call_special(
+        &self,
+        token: &Token,
+        receiver: &LiteralValue,
+        name: &str,
+        arguments: Vec<LiteralValue>,
+    ) -> Result<Option<LiteralValue>, LoxResult> {
+        let method = match receiver {
+            LiteralValue::Instance(inst) => inst.bound_method(name, inst),
+            _ => None,
+        };
+        if let Some(LiteralValue::Func(method)) = method {
+            if !method.arity().accepts(arguments.len()) {
+                return Err(LoxResult::runtime_error(
+                    token,
+                    &format!(
+                        "Special method '{name}' must take {} arguments but takes {}.",
+                        arguments.len(),
+                        method.arity()
+                    ),
+                ));
+            }
+            Ok(Some(method.call(self, arguments, None)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Collects the values bound by the pattern's names as it goes. They are
+    // only used if the whole pattern matches.
+    fn match_pattern(
+        &self,
+        pattern: &Pattern,
+        value: &LiteralValue,
+        bindings: &mut Vec<(String, LiteralValue)>,
+    ) -> Result<bool, LoxResult> {
+        match pattern {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Binding(name) => {
+                bindings.push((name.as_string(), value.clone()));
+                Ok(true)
+            }
+            Pattern::Literal { token, value: expected } => self.values_equal(token, value, expected),
+            Pattern::Constant { token, value: expected } => {
+                let expected = self.evaluate(expected.clone())?;
+                self.values_equal(token, value, &expected)
+            }
+            Pattern::Alternatives(alternatives) => {
+                for alternative in alternatives {
+                    if self.match_pattern(alternative, value, bindings)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Pattern::Instance { class, paren, fields } => {
+                let klass = match self.evaluate(class.clone())? {
+                    LiteralValue::Class(klass) => klass,
+                    LiteralValue::Variant(variant) => {
+                        return self.match_variant(&variant, paren, fields, value, bindings)
+                    }
+                    _ => return Err(LoxResult::runtime_error(paren, "Can only match instances of a class.")),
+                };
+                let inst = match value {
+                    LiteralValue::Instance(inst) if inst.is_instance_of(&klass) => inst,
+                    _ => return Ok(false),
+                };
+                let names = klass.field_order();
+                if fields.len() > names.len() {
+                    return Err(LoxResult::runtime_error(
+                        paren,
+                        &format!(
+                            "Pattern has {} fields but {}'s initializer takes {}.",
+                            fields.len(),
+                            klass,
+                            names.len()
+                        ),
+                    ));
+                }
+                for (field, name) in fields.iter().zip(names.iter()) {
+                    let field_value = inst.get(name, inst, self)?;
+                    if !self.match_pattern(field, &field_value, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    fn match_variant(
+        &self,
+        variant: &Rc<LoxVariant>,
+        paren: &Token,
+        fields: &[Pattern],
+        value: &LiteralValue,
+        bindings: &mut Vec<(String, LiteralValue)>,
+    ) -> Result<bool, LoxResult> {
+        let enum_value = match value {
+            LiteralValue::EnumValue(enum_value) if enum_value.is_variant(variant) => enum_value,
+            _ => return Ok(false),
+        };
+        if fields.len() > variant.field_count() {
+            return Err(LoxResult::runtime_error(
+                paren,
+                &format!(
+                    "Pattern has {} fields but {} has {}.",
+                    fields.len(),
+                    variant,
+                    variant.field_count()
+                ),
+            ));
+        }
+        for (field, field_value) in fields.iter().zip(enum_value.values.iter()) {
+            if !self.match_pattern(field, field_value, bindings)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn values_equal(&self, token: &Token, a: &LiteralValue, b: &LiteralValue) -> Result<bool, LoxResult> {
+        let equal = Token::new(TokenType::EQUAL_EQUAL, "==".to_string(), None, token.line);
+        let result = self.binary_op(&equal, a.clone(), b.clone())?;
+        Ok(self.is_truthy(&result))
+    }
+
+    fn enum_values_equal(&self, token: &Token, a: &LoxEnumValue, b: &LoxEnumValue) -> Result<bool, LoxResult> {
+        if !Rc::ptr_eq(&a.variant, &b.variant) {
+            return Ok(false);
+        }
+        for (x, y) in a.values.iter().zip(b.values.iter()) {
+            if !self.values_equal(token, x, y)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // The text `print`, concatenation and interpolation use for a value. A
+    // class can supply its own by defining a `toString()` method.
+    pub fn stringify(&self, value: &LiteralValue) -> Result<String, LoxResult> {
+        match value {
+            LiteralValue::Instance(inst) => {
+                if let Some(LiteralValue::Func(method)) = inst.bound_method("toString", inst) {
+                    if method.arity().accepts(0) {
+                        return Ok(method.call(self, Vec::new(), None)?.to_string());
+                    }
+                }
+            }
+            // The elements are copied out first, since a `toString()` may
+            // change the collection while it is being printed.
+            LiteralValue::List(list) => {
+                let Some(_guard) = PrintGuard::enter(list.as_ref()) else {
+                    return Ok("[...]".to_string());
+                };
+                let elements = list.elements.borrow().clone();
+                let elements = elements
+                    .iter()
+                    .map(|element| self.stringify_element(element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                return Ok(format!("[{}]", elements.join(", ")));
+            }
+            LiteralValue::Map(map) => {
+                let Some(_guard) = PrintGuard::enter(map.as_ref()) else {
+                    return Ok("{...}".to_string());
+                };
+                let entries = map
+                    .entries()
+                    .iter()
+                    .map(|(key, value)| Ok(format!("{}: {}", key.repr(), self.stringify_element(value)?)))
+                    .collect::<Result<Vec<_>, LoxResult>>()?;
+                return Ok(format!("{{{}}}", entries.join(", ")));
+            }
+            _ => {}
+        }
+        Ok(value.to_string())
+    }
+
+    // Strings inside a collection are quoted, as `repr` does.
+    fn stringify_element(&self, value: &LiteralValue) -> Result<String, LoxResult> {
+        match value {
+            LiteralValue::Str(_) => Ok(value.repr()),
+            _ => self.stringify(value),
+        }
+    }
+
+    // Built-in runtime errors reach a catch block as an Error instance with
+    // the message and line that would otherwise have been reported.
+    fn error_value(&self, token: &Token, message: &str) -> LiteralValue {
+        let instance = LoxInstance::new(Rc::clone(&self.error_class));
+        let field = |name: &str| Token::new(TokenType::IDENTIFIER, name.to_string(), None, token.line);
+        instance.set(&field("message"), LiteralValue::Str(message.to_string()));
+        instance.set(&field("line"), LiteralValue::Int(token.line as i64));
+        LiteralValue::Instance(Rc::new(instance))
+    }
+
+    fn float_op(&self, operator: &Token, left: f64, right: f64) -> Result<LiteralValue, LoxResult> {
+        Ok(match operator.token_type() {
+            TokenType::MINUS => LiteralValue::Num(left - right),
+            TokenType::SLASH => LiteralValue::Num(left / right),
+            TokenType::STAR => LiteralValue::Num(left * right),
+            TokenType::PLUS => LiteralValue::Num(left + right),
+            TokenType::STAR_STAR => LiteralValue::Num(left.powf(right)),
+            TokenType::PERCENT | TokenType::TILDE_SLASH if right == 0.0 => {
+                return Err(LoxResult::runtime_error(operator, "Division by zero."));
+            }
+            // Modulo takes the sign of the divisor, so -1 % 3 is 2.
+            TokenType::PERCENT => LiteralValue::Num(left - right * (left / right).floor()),
+            TokenType::TILDE_SLASH => LiteralValue::Num((left / right).floor()),
+            TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::LESS_LESS
+            | TokenType::GREATER_GREATER => {
+                let a = self.integral(operator, left)?;
+                let b = self.integral(operator, right)?;
+                LiteralValue::Int(self.bitwise(operator, a, b)?)
+            }
+            TokenType::GREATER => LiteralValue::Bool(left > right),
+            TokenType::GREATER_EQUAL => LiteralValue::Bool(left >= right),
+            TokenType::LESS => LiteralValue::Bool(left < right),
+            TokenType::LESS_EQUAL => LiteralValue::Bool(left <= right),
+            TokenType::BANG_EQUAL => LiteralValue::Bool(left != right),
+            TokenType::EQUAL_EQUAL => LiteralValue::Bool(left == right),
+            _ => {
+                return Err(LoxResult::runtime_error(
+                    operator,
+                    "Unsupported operator for numbers.",
+                ))
+            }
+        })
+    }
+
+    // Integer arithmetic is exact, redoing an operation that overflows with
+    // big integers rather than wrapping. Only `/` and negative powers leave
+    // the integers for floats.
+    fn int_op(&self, operator: &Token, left: i64, right: i64) -> Result<LiteralValue, LoxResult> {
+        let checked = |result: Option<i64>| match result {
+            Some(n) => Ok(LiteralValue::Int(n)),
+            None => self.big_op(operator, &BigInt::from_i64(left), &BigInt::from_i64(right)),
+        };
+        match operator.token_type() {
+            TokenType::MINUS => checked(left.checked_sub(right)),
+            TokenType::STAR => checked(left.checked_mul(right)),
+            TokenType::PLUS => checked(left.checked_add(right)),
+            TokenType::SLASH => self.float_op(operator, left as f64, right as f64),
+            TokenType::STAR_STAR if right < 0 => self.float_op(operator, left as f64, right as f64),
+            TokenType::STAR_STAR => {
+                checked(u32::try_from(right).ok().and_then(|exponent| left.checked_pow(exponent)))
+            }
+            TokenType::PERCENT | TokenType::TILDE_SLASH if right == 0 => {
+                Err(LoxResult::runtime_error(operator, "Division by zero."))
+            }
+            // Both round towards negative infinity, as they do for floats.
+            TokenType::PERCENT => checked(left.checked_rem(right).map(|r| {
+                if r != 0 && (r < 0) != (right < 0) {
+                    r + right
+                } else {
+                    r
+                }
+            })),
+            TokenType::TILDE_SLASH => checked(left.checked_div(right).map(|q| {
+                if left % right != 0 && (left < 0) != (right < 0) {
+                    q - 1
+                } else {
+                    q
+                }
+            })),
+            TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::LESS_LESS
+            | TokenType::GREATER_GREATER => Ok(LiteralValue::Int(self.bitwise(operator, left, right)?)),
+            TokenType::GREATER => Ok(LiteralValue::Bool(left > right)),
+            TokenType::GREATER_EQUAL => Ok(LiteralValue::Bool(left >= right)),
+            TokenType::LESS => Ok(LiteralValue::Bool(left < right)),
+            TokenType::LESS_EQUAL => Ok(LiteralValue::Bool(left <= right)),
+            TokenType::BANG_EQUAL => Ok(LiteralValue::Bool(left != right)),
+            TokenType::EQUAL_EQUAL => Ok(LiteralValue::Bool(left == right)),
+            _ => Err(LoxResult::runtime_error(
+                operator,
+                "Unsupported operator for integers.",
+            )),
+        }
+    }
+
+    // Results that fit in an i64 go back to being plain integers.
+    fn big_op(&self, operator: &Token, left: &BigInt, right: &BigInt) -> Result<LiteralValue, LoxResult> {
+        Ok(match operator.token_type() {
+            TokenType::MINUS => LiteralValue::integer(left - right),
+            TokenType::STAR => LiteralValue::integer(left * right),
+            TokenType::PLUS => LiteralValue::integer(left + right),
+            TokenType::SLASH => return self.float_op(operator, left.to_f64(), right.to_f64()),
+            TokenType::STAR_STAR if right.is_negative() => {
+                return self.float_op(operator, left.to_f64(), right.to_f64())
+            }
+            TokenType::STAR_STAR => {
+                let power = right
+                    .to_i64()
+                    .and_then(|n| u32::try_from(n).ok())
+                    .and_then(|exponent| left.checked_pow(exponent));
+                match power {
+                    Some(power) => LiteralValue::integer(power),
+                    None => return Err(LoxResult::runtime_error(operator, "Exponent is too large.")),
+                }
+            }
+            TokenType::PERCENT | TokenType::TILDE_SLASH => {
+                let (quotient, remainder) = left
+                    .div_rem_floor(right)
+                    .ok_or_else(|| LoxResult::runtime_error(operator, "Division by zero."))?;
+                if operator.is(TokenType::PERCENT) {
+                    LiteralValue::integer(remainder)
+                } else {
+                    LiteralValue::integer(quotient)
+                }
+            }
+            TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::LESS_LESS
+            | TokenType::GREATER_GREATER => match (left.to_i64(), right.to_i64()) {
+                (Some(a), Some(b)) => LiteralValue::Int(self.bitwise(operator, a, b)?),
+                _ => {
+                    return Err(LoxResult::runtime_error(
+                        operator,
+                        "Bitwise operands must fit in 64 bits.",
+                    ))
+                }
+            },
+            TokenType::GREATER => LiteralValue::Bool(left > right),
+            TokenType::GREATER_EQUAL => LiteralValue::Bool(left >= right),
+            TokenType::LESS => LiteralValue::Bool(left < right),
+            TokenType::LESS_EQUAL => LiteralValue::Bool(left <= right),
+            TokenType::BANG_EQUAL => LiteralValue::Bool(left != right),
+            TokenType::EQUAL_EQUAL => LiteralValue::Bool(left == right),
+            _ => {
+                return Err(LoxResult::runtime_error(
+                    operator,
+                    "Unsupported operator for integers.",
+                ))
+            }
+        })
+    }
+
+    // Decimal arithmetic is exact except for division, which keeps the
+    // places the decimal context asks for when the quotient doesn't end.
+    fn decimal_op(&self, operator: &Token, left: &Decimal, right: &Decimal) -> Result<LiteralValue, LoxResult> {
+        let decimal = |d: Decimal| LiteralValue::Decimal(Rc::new(d));
+        let division_by_zero = || LoxResult::runtime_error(operator, "Division by zero.");
+        Ok(match operator.token_type() {
+            TokenType::MINUS => decimal(left - right),
+            TokenType::STAR => decimal(left * right),
+            TokenType::PLUS => decimal(left + right),
+            TokenType::SLASH => decimal(
+                left.divide(right, self.decimal_context.get())
+                    .ok_or_else(division_by_zero)?,
+            ),
+            TokenType::STAR_STAR => {
+                let exponent = match right.to_bigint().to_i64() {
+                    Some(exponent) if right.is_integral() => exponent,
+                    _ => {
+                        return Err(LoxResult::runtime_error(
+                            operator,
+                            "Decimal exponents must be whole numbers.",
+                        ))
+                    }
+                };
+                let power = u32::try_from(exponent.unsigned_abs())
+                    .ok()
+                    .and_then(|magnitude| left.pow(magnitude));
+                let power = match power {
+                    Some(power) => power,
+                    None => return Err(LoxResult::runtime_error(operator, "Exponent is too large.")),
+                };
+                if exponent < 0 {
+                    let one = Decimal::from_bigint(BigInt::from_i64(1));
+                    decimal(
+                        one.divide(&power, self.decimal_context.get())
+                            .ok_or_else(division_by_zero)?,
+                    )
+                } else {
+                    decimal(power)
+                }
+            }
+            TokenType::PERCENT | TokenType::TILDE_SLASH => {
+                let (quotient, remainder) = left.div_rem_floor(right).ok_or_else(division_by_zero)?;
+                if operator.is(TokenType::PERCENT) {
+                    decimal(remainder)
+                } else {
+                    decimal(Decimal::from_bigint(quotient))
+                }
+            }
+            TokenType::AMPERSAND
+            | TokenType::PIPE
+            | TokenType::CARET
+            | TokenType::LESS_LESS
+            | TokenType::GREATER_GREATER => {
+                return Err(LoxResult::runtime_error(
+                    operator,
+                    "Bitwise operands can't be decimals.",
+                ))
+            }
+            TokenType::GREATER => LiteralValue::Bool(left > right),
+            TokenType::GREATER_EQUAL => LiteralValue::Bool(left >= right),
+            TokenType::LESS => LiteralValue::Bool(left < right),
+            TokenType::LESS_EQUAL => LiteralValue::Bool(left <= right),
+            TokenType::BANG_EQUAL => LiteralValue::Bool(left != right),
+            TokenType::EQUAL_EQUAL => LiteralValue::Bool(left == right),
+            _ => {
+                return Err(LoxResult::runtime_error(
+                    operator,
+                    "Unsupported operator for decimals.",
+                ))
+            }
+        })
+    }
+
+    pub fn decimal_context(&self) -> DecimalContext {
+        self.decimal_context.get()
+    }
+
+    pub fn set_decimal_context(&self, context: DecimalContext) {
+        self.decimal_context.set(context);
+    }
+
+    // Bitwise operators work on whole numbers that fit in an i64.
+    fn integral(&self, operator: &Token, n: f64) -> Result<i64, LoxResult> {
+        if n.fract() == 0.0 && n >= i64::MIN as f64 && n <= i64::MAX as f64 {
+            Ok(n as i64)
+        } else {
+            Err(LoxResult::runtime_error(
+                operator,
+                &format!("Bitwise operands must be integers, got {n}."),
+            ))
+        }
+    }
+
+    fn bitwise(&self, operator: &Token, a: i64, b: i64) -> Result<i64, LoxResult> {
+        match operator.token_type() {
+            TokenType::AMPERSAND => Ok(a & b),
+            TokenType::PIPE => Ok(a | b),
+            TokenType::CARET => Ok(a ^ b),
+            TokenType::LESS_LESS | TokenType::GREATER_GREATER if !(0..64).contains(&b) => {
+                Err(LoxResult::runtime_error(
+                    operator,
+                    "Shift amount must be between 0 and 63.",
+                ))
+            }
+            TokenType::LESS_LESS => Ok(a << b),
+            _ => Ok(a >> b),
+        }
+    }
+
     // Anything that is not Nil or False is true
-    fn is_truthy(&self, literal_value: &LiteralValue) -> bool {
+    pub fn is_truthy(&self, literal_value: &LiteralValue) -> bool {
         !matches!(literal_value, LiteralValue::Nil | LiteralValue::Bool(false))
     }
     pub fn interpret(&self, statements: &[Rc<Stmt>]) -> Result<(), LoxResult> {
@@ -472,7 +2023,7 @@ impl Interpreter {
                 .borrow()
                 .get_at(*distance, &name.as_string())
         } else {
-            self.globals.borrow().get(name)
+            self.environment.borrow().borrow().get_global(name)
         }
 }
 }
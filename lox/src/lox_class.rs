@@ -1,22 +1,38 @@
+use std::cell::RefCell;
 use std::rc::Rc;
 use std::collections::HashMap;
 use std::fmt;
 
-use crate::{callable::LoxCallable, entities::LiteralValue, lox_instance::LoxInstance, Interpreter, LoxResult};
+use crate::{callable::{Arity, LoxCallable}, entities::{LiteralValue, Token}, lox_instance::LoxInstance, Interpreter, LoxResult};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct LoxClass {
     name: String,
     methods: HashMap<String, LiteralValue>,
     superclass: Option<Rc<LoxClass>>,
+    static_methods: HashMap<String, LiteralValue>,
+    static_fields: RefCell<HashMap<String, LiteralValue>>,
+    getters: HashMap<String, LiteralValue>,
+    setters: HashMap<String, LiteralValue>,
 }
 
 impl LoxClass {
-    pub fn new(name: &str, superclass: Option<Rc<LoxClass>>, methods: HashMap<String, LiteralValue>) -> Self {
+    pub fn new(
+        name: &str,
+        superclass: Option<Rc<LoxClass>>,
+        methods: HashMap<String, LiteralValue>,
+        static_methods: HashMap<String, LiteralValue>,
+        getters: HashMap<String, LiteralValue>,
+        setters: HashMap<String, LiteralValue>,
+    ) -> Self {
         Self {
             name: name.to_string(),
             methods,
             superclass,
+            static_methods,
+            static_fields: RefCell::new(HashMap::new()),
+            getters,
+            setters,
         }
     }
 
@@ -24,13 +40,19 @@ impl LoxClass {
         &self,
         interpreter: &Interpreter,
         arguments: Vec<LiteralValue>,
+        named: Vec<(Token, LiteralValue)>,
         klass: Rc<LoxClass>,
     ) -> Result<LiteralValue, LoxResult> {
         let instance = LiteralValue::Instance(Rc::new(LoxInstance::new(klass)));
         if let Some(LiteralValue::Func(initializer)) = self.find_method("init") {
             if let LiteralValue::Func(init) = initializer.bind(&instance) {
-                init.call(interpreter, arguments,None)?;
+                init.call_named(interpreter, arguments, named, None)?;
             }
+        } else if let Some((name, _)) = named.first() {
+            return Err(LoxResult::runtime_error(
+                name,
+                &format!("{} has no initializer to take named arguments.", self.name),
+            ));
         }
         Ok(instance)
     }
@@ -46,6 +68,88 @@ impl LoxClass {
         }
 
     }
+
+    // Instance patterns in a match read fields in the order of the
+    // initializer's parameters.
+    pub fn field_order(&self) -> Rc<Vec<Token>> {
+        if let Some(LiteralValue::Func(initializer)) = self.find_method("init") {
+            initializer.params()
+        } else {
+            Rc::new(Vec::new())
+        }
+    }
+
+    pub fn is_subclass_of(&self, other: &LoxClass) -> bool {
+        if std::ptr::eq(self, other) {
+            true
+        } else if let Some(superclass) = &self.superclass {
+            superclass.is_subclass_of(other)
+        } else {
+            false
+        }
+    }
+
+    pub fn find_getter(&self, name: &str) -> Option<LiteralValue> {
+        if let Some(getter) = self.getters.get(name) {
+            Some(getter.clone())
+        } else if let Some(superclass) = &self.superclass {
+            superclass.find_getter(name)
+        } else {
+            None
+        }
+    }
+
+    pub fn find_setter(&self, name: &str) -> Option<LiteralValue> {
+        if let Some(setter) = self.setters.get(name) {
+            Some(setter.clone())
+        } else if let Some(superclass) = &self.superclass {
+            superclass.find_setter(name)
+        } else {
+            None
+        }
+    }
+
+    pub fn find_static_method(&self, name: &str) -> Option<LiteralValue> {
+        if let Some(method) = self.static_methods.get(name) {
+            Some(method.clone())
+        } else if let Some(superclass) = &self.superclass {
+            superclass.find_static_method(name)
+        } else {
+            None
+        }
+    }
+
+    fn find_static_field(&self, name: &str) -> Option<LiteralValue> {
+        if let Some(value) = self.static_fields.borrow().get(name) {
+            Some(value.clone())
+        } else if let Some(superclass) = &self.superclass {
+            superclass.find_static_field(name)
+        } else {
+            None
+        }
+    }
+
+    // Static fields and methods are looked up through the superclass chain
+    // like instance methods. A static method's `this` is the class it was
+    // accessed through, not necessarily the one that declared it.
+    pub fn get(&self, name: &Token, this: &Rc<LoxClass>) -> Result<LiteralValue, LoxResult> {
+        if let Some(value) = self.find_static_field(&name.as_string()) {
+            Ok(value)
+        } else if let Some(LiteralValue::Func(func)) = self.find_static_method(&name.as_string()) {
+            Ok(func.bind(&LiteralValue::Class(Rc::clone(this))))
+        } else {
+            Err(LoxResult::runtime_error(
+                name,
+                &format!("Undefined property '{}'.", name.as_string()),
+            ))
+        }
+    }
+
+    // Assigning through a subclass gives it its own copy of the field
+    // rather than changing the one it inherited.
+    pub fn set(&self, name: &Token, value: LiteralValue) {
+        self.static_fields.borrow_mut().insert(name.as_string(), value);
+    }
 }
 
 impl fmt::Display for LoxClass {
@@ -61,13 +165,22 @@ impl LoxCallable for LoxClass {
         arguments: Vec<LiteralValue>,
         klass: Option<Rc<LoxClass>>,
     ) -> Result<LiteralValue, LoxResult> {
-        self.instantiate(interpreter, arguments, klass.unwrap())
+        self.instantiate(interpreter, arguments, Vec::new(), klass.unwrap())
+    }
+    fn call_named(
+        &self,
+        interpreter: &Interpreter,
+        arguments: Vec<LiteralValue>,
+        named: Vec<(Token, LiteralValue)>,
+        klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        self.instantiate(interpreter, arguments, named, klass.unwrap())
     }
-    fn arity(&self) -> usize {
+    fn arity(&self) -> Arity {
         if let Some(LiteralValue::Func(initializer)) = self.find_method("init") {
             initializer.arity()
         } else {
-            0
+            Arity::exact(0)
         }
     }
 }
@@ -0,0 +1,277 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::lox_bigint::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    HalfEven,
+    HalfUp,
+    HalfDown,
+    Up,
+    Down,
+    Ceiling,
+    Floor,
+}
+
+impl Rounding {
+    pub fn parse(name: &str) -> Option<Rounding> {
+        match name {
+            "half_even" => Some(Rounding::HalfEven),
+            "half_up" => Some(Rounding::HalfUp),
+            "half_down" => Some(Rounding::HalfDown),
+            "up" => Some(Rounding::Up),
+            "down" => Some(Rounding::Down),
+            "ceiling" => Some(Rounding::Ceiling),
+            "floor" => Some(Rounding::Floor),
+            _ => None,
+        }
+    }
+}
+
+// How many decimal places a division that doesn't terminate keeps, and how
+// it rounds the last one. Set from scripts with decimalContext(), which
+// allows at most MAX_PLACES.
+#[derive(Debug, Clone, Copy)]
+pub struct DecimalContext {
+    pub places: u32,
+    pub rounding: Rounding,
+}
+
+pub const MAX_PLACES: u32 = 10_000;
+
+impl Default for DecimalContext {
+    fn default() -> Self {
+        Self { places: 28, rounding: Rounding::HalfEven }
+    }
+}
+
+// coefficient / 10^scale. The scale is kept, so 0.10 + 0.20 prints as 0.30
+// and money amounts keep their cents.
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    coefficient: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn from_bigint(n: BigInt) -> Self {
+        Self { coefficient: n, scale: 0 }
+    }
+
+    // An optional sign, digits, and optionally a point and more digits.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        if whole.trim_start_matches(['-', '+']).is_empty() && fraction.is_empty() {
+            return None;
+        }
+        let digits = if whole.trim_start_matches(['-', '+']).is_empty() {
+            format!("{whole}0{fraction}")
+        } else {
+            format!("{whole}{fraction}")
+        };
+        Some(Self {
+            coefficient: BigInt::parse(&digits)?,
+            scale: fraction.len() as u32,
+        })
+    }
+
+    pub fn is_integral(&self) -> bool {
+        self.truncated().1.is_zero()
+    }
+
+    // The whole part, rounding towards zero, and what's left of the
+    // coefficient.
+    fn truncated(&self) -> (BigInt, BigInt) {
+        self.coefficient.div_rem(&BigInt::pow10(self.scale)).unwrap()
+    }
+
+    pub fn to_bigint(&self) -> BigInt {
+        self.truncated().0
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap()
+    }
+
+    fn rescaled(&self, scale: u32) -> BigInt {
+        &self.coefficient * &BigInt::pow10(scale - self.scale)
+    }
+
+    fn aligned(&self, other: &Decimal) -> (BigInt, BigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        (self.rescaled(scale), other.rescaled(scale), scale)
+    }
+
+    // Exact when the quotient terminates within the context's places,
+    // otherwise rounded there. Trailing zeros are dropped back to the scale
+    // the operands imply. None when dividing by zero.
+    pub fn divide(&self, divisor: &Decimal, context: DecimalContext) -> Option<Decimal> {
+        let places = context.places.max(self.scale.saturating_sub(divisor.scale));
+        let numerator = &self.coefficient * &BigInt::pow10(divisor.scale + places);
+        let denominator = &divisor.coefficient * &BigInt::pow10(self.scale);
+        let coefficient = round_division(&numerator, &denominator, context.rounding)?;
+
+        let ideal = self.scale.saturating_sub(divisor.scale);
+        Some(Decimal { coefficient, scale: places }.trimmed_to(ideal))
+    }
+
+    // The value with exactly `places` decimal places.
+    pub fn round(&self, places: u32, rounding: Rounding) -> Decimal {
+        if places >= self.scale {
+            return Decimal { coefficient: self.rescaled(places), scale: places };
+        }
+        let divisor = BigInt::pow10(self.scale - places);
+        Decimal {
+            coefficient: round_division(&self.coefficient, &divisor, rounding).unwrap(),
+            scale: places,
+        }
+    }
+
+    // The floored quotient, as an integer, and the remainder, for `~/`
+    // and `%`.
+    pub fn div_rem_floor(&self, divisor: &Decimal) -> Option<(BigInt, Decimal)> {
+        let (a, b, scale) = self.aligned(divisor);
+        let (quotient, remainder) = a.div_rem_floor(&b)?;
+        Some((quotient, Decimal { coefficient: remainder, scale }))
+    }
+
+    // The same value without trailing zeros.
+    pub fn normalized(&self) -> Decimal {
+        self.clone().trimmed_to(0)
+    }
+
+    // Drops trailing zeros, keeping at least `scale` places.
+    fn trimmed_to(self, scale: u32) -> Decimal {
+        let ten = BigInt::from_i64(10);
+        let mut result = self;
+        while result.scale > scale {
+            match result.coefficient.div_rem(&ten) {
+                Some((quotient, remainder)) if remainder.is_zero() => {
+                    result = Decimal { coefficient: quotient, scale: result.scale - 1 };
+                }
+                _ => break,
+            }
+        }
+        result
+    }
+
+    // None when the coefficient or the number of places would be too large.
+    pub fn pow(&self, exponent: u32) -> Option<Decimal> {
+        let scale = self.scale.checked_mul(exponent)?;
+        if (scale as f64 * 10f64.log2()) as u64 > MAX_POWER_BITS {
+            return None;
+        }
+        Some(Decimal {
+            coefficient: self.coefficient.checked_pow(exponent)?,
+            scale,
+        })
+    }
+}
+
+// numerator / denominator rounded to an integer.
+fn round_division(numerator: &BigInt, denominator: &BigInt, rounding: Rounding) -> Option<BigInt> {
+    let (quotient, remainder) = numerator.div_rem(denominator)?;
+    if remainder.is_zero() {
+        return Some(quotient);
+    }
+    let negative = numerator.is_negative() != denominator.is_negative();
+    let half = (&remainder.abs() * &BigInt::from_i64(2)).cmp(&denominator.abs());
+    let away = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => true,
+        Rounding::Ceiling => !negative,
+        Rounding::Floor => negative,
+        Rounding::HalfUp => half != Ordering::Less,
+        Rounding::HalfDown => half == Ordering::Greater,
+        Rounding::HalfEven => half == Ordering::Greater || (half == Ordering::Equal && quotient.is_odd()),
+    };
+    if !away {
+        Some(quotient)
+    } else if negative {
+        Some(&quotient - &BigInt::from_i64(1))
+    } else {
+        Some(&quotient + &BigInt::from_i64(1))
+    }
+}
+
+impl Add for &Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: &Decimal) -> Decimal {
+        let (a, b, scale) = self.aligned(other);
+        Decimal { coefficient: &a + &b, scale }
+    }
+}
+
+impl Sub for &Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: &Decimal) -> Decimal {
+        let (a, b, scale) = self.aligned(other);
+        Decimal { coefficient: &a - &b, scale }
+    }
+}
+
+impl Mul for &Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: &Decimal) -> Decimal {
+        Decimal {
+            coefficient: &self.coefficient * &other.coefficient,
+            scale: self.scale + other.scale,
+        }
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal { coefficient: -&self.coefficient, scale: self.scale }
+    }
+}
+
+// Equal values are equal whatever their scale, so 0.5 == 0.50.
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, _) = self.aligned(other);
+        a.cmp(&b)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.coefficient.abs().to_string();
+        let scale = self.scale as usize;
+        let digits = format!("{digits:0>width$}", width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        if self.coefficient.is_negative() {
+            write!(f, "-")?;
+        }
+        if fraction.is_empty() {
+            write!(f, "{whole}")
+        } else {
+            write!(f, "{whole}.{fraction}")
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::callable::*;
+use crate::entities::*;
+use crate::errors::*;
+use crate::interpreter::*;
+use crate::lox_class::*;
+
+// An enum's variants in declaration order. A variant without fields is a
+// single shared value, and one with fields is a constructor for values
+// such as `Shape.Circle(2)`.
+pub struct LoxEnum {
+    name: String,
+    variants: Vec<(String, LiteralValue)>,
+}
+
+impl fmt::Debug for LoxEnum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl PartialEq for LoxEnum {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl LoxEnum {
+    // Each variant comes with its field names, or None if it has no fields.
+    pub fn new(name: &str, variants: Vec<(String, Option<Vec<String>>)>) -> Self {
+        let variants = variants
+            .into_iter()
+            .map(|(variant_name, fields)| {
+                let variant = Rc::new(LoxVariant {
+                    enum_name: name.to_string(),
+                    name: variant_name.clone(),
+                    fields: fields.clone().unwrap_or_default(),
+                });
+                let value = match fields {
+                    Some(_) => LiteralValue::Variant(variant),
+                    None => LiteralValue::EnumValue(Rc::new(LoxEnumValue {
+                        variant,
+                        values: Vec::new(),
+                    })),
+                };
+                (variant_name, value)
+            })
+            .collect();
+        Self {
+            name: name.to_string(),
+            variants,
+        }
+    }
+
+    pub fn get(&self, name: &Token) -> Result<LiteralValue, LoxResult> {
+        self.variants
+            .iter()
+            .find(|(variant, _)| *variant == name.as_string())
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| {
+                LoxResult::runtime_error(
+                    name,
+                    &format!("Enum '{}' has no variant '{}'.", self.name, name.as_string()),
+                )
+            })
+    }
+
+    // What iterating over the enum yields.
+    pub fn variants(&self) -> Vec<LiteralValue> {
+        self.variants.iter().map(|(_, value)| value.clone()).collect()
+    }
+}
+
+impl fmt::Display for LoxEnum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<enum {}>", self.name)
+    }
+}
+
+// Variants are compared by identity, so that variants of two enums that
+// happen to share names are never equal.
+#[derive(Debug)]
+pub struct LoxVariant {
+    enum_name: String,
+    name: String,
+    fields: Vec<String>,
+}
+
+impl PartialEq for LoxVariant {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl LoxVariant {
+    pub fn constructor(self: &Rc<Self>) -> Rc<dyn LoxCallable> {
+        Rc::new(VariantConstructor { variant: Rc::clone(self) })
+    }
+
+    pub fn field_count(&self) -> usize {
+        self.fields.len()
+    }
+}
+
+impl fmt::Display for LoxVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.enum_name, self.name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LoxEnumValue {
+    pub variant: Rc<LoxVariant>,
+    pub values: Vec<LiteralValue>,
+}
+
+impl LoxEnumValue {
+    pub fn is_variant(&self, variant: &Rc<LoxVariant>) -> bool {
+        Rc::ptr_eq(&self.variant, variant)
+    }
+
+    // Values are immutable, so their fields can be read but not set.
+    pub fn get(&self, name: &Token) -> Result<LiteralValue, LoxResult> {
+        self.variant
+            .fields
+            .iter()
+            .position(|field| *field == name.as_string())
+            .map(|index| self.values[index].clone())
+            .ok_or_else(|| {
+                LoxResult::runtime_error(
+                    name,
+                    &format!("Undefined property '{}'.", name.as_string()),
+                )
+            })
+    }
+}
+
+impl fmt::Display for LoxEnumValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.variant.fields.is_empty() {
+            return write!(f, "{}", self.variant);
+        }
+        let values: Vec<String> = self.values.iter().map(LiteralValue::repr).collect();
+        write!(f, "{}({})", self.variant, values.join(", "))
+    }
+}
+
+struct VariantConstructor {
+    variant: Rc<LoxVariant>,
+}
+
+impl LoxCallable for VariantConstructor {
+    fn call(
+        &self,
+        _interpreter: &Interpreter,
+        arguments: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        Ok(LiteralValue::EnumValue(Rc::new(LoxEnumValue {
+            variant: Rc::clone(&self.variant),
+            values: arguments,
+        })))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(self.variant.fields.len())
+    }
+}
@@ -9,12 +9,18 @@ use crate::callable::*;
 use crate::errors::*;
 use crate::environment::*;
 use crate::lox_class::*;
+use crate::lox_generator::*;
+use crate::lox_list::*;
+use crate::expr::*;
 
 pub struct LoxFunction {
     name: Token,
     is_initializer: bool,
     params: Rc<Vec<Token>>,
+    defaults: Rc<Vec<Option<Rc<Expr>>>>,
+    rest: Option<Token>,
     body: Rc<Vec<Rc<Stmt>>>,
+    is_generator: bool,
     closure: Rc<RefCell<Environment>>,
 
 }
@@ -30,7 +36,10 @@ impl Clone for LoxFunction {
             name: self.name.dup(),
             is_initializer: self.is_initializer,
             params: Rc::clone(&self.params),
+            defaults: Rc::clone(&self.defaults),
+            rest: self.rest.as_ref().map(Token::dup),
             body: Rc::clone(&self.body),
+            is_generator: self.is_generator,
             closure: Rc::clone(&self.closure),
         }
     }
@@ -54,10 +63,17 @@ impl LoxFunction {
             name: declaration.name.dup(),
             is_initializer,
             params: Rc::clone(&declaration.params),
+            defaults: Rc::clone(&declaration.defaults),
+            rest: declaration.rest.as_ref().map(Token::dup),
             body: Rc::clone(&declaration.body),
+            is_generator: declaration.is_generator,
             closure: Rc::clone(closure),
         }
     }
+    pub fn params(&self) -> Rc<Vec<Token>> {
+        Rc::clone(&self.params)
+    }
+
     pub fn bind(&self, instance: &LiteralValue) -> LiteralValue {
         let environment = RefCell::new(Environment::new_with_enclosing(Rc::clone(&self.closure)));
         environment.borrow_mut().define("this", instance.clone());
@@ -65,7 +81,10 @@ impl LoxFunction {
             name: self.name.dup(),
             is_initializer: self.is_initializer,
             params: Rc::clone(&self.params),
+            defaults: Rc::clone(&self.defaults),
+            rest: self.rest.as_ref().map(Token::dup),
             body: Rc::clone(&self.body),
+            is_generator: self.is_generator,
             closure: Rc::new(environment),
         }))
     }
@@ -77,12 +96,77 @@ impl LoxCallable for LoxFunction {
         &self,
         interpreter: &Interpreter,
         arguments: Vec<LiteralValue>,
+        klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        self.call_named(interpreter, arguments, Vec::new(), klass)
+    }
+
+    fn call_named(
+        &self,
+        interpreter: &Interpreter,
+        arguments: Vec<LiteralValue>,
+        named: Vec<(Token, LiteralValue)>,
         _klass: Option<Rc<LoxClass>>,
     ) -> Result<LiteralValue, LoxResult> {
+        let count = arguments.len() + named.len();
+        let mut slots: Vec<Option<LiteralValue>> = vec![None; self.params.len()];
+        let mut extra = Vec::new();
+        for (i, argument) in arguments.into_iter().enumerate() {
+            if i < slots.len() {
+                slots[i] = Some(argument);
+            } else {
+                extra.push(argument);
+            }
+        }
+        if !extra.is_empty() && self.rest.is_none() {
+            return Err(LoxResult::system_error(&format!(
+                "Expected {} arguments but got {count}.",
+                self.arity()
+            )));
+        }
+
+        for (name, value) in named {
+            match self.params.iter().position(|param| param.as_string() == name.as_string()) {
+                Some(i) if slots[i].is_some() => {
+                    return Err(LoxResult::runtime_error(
+                        &name,
+                        &format!("Argument '{}' was already passed by position.", name.as_string()),
+                    ));
+                }
+                Some(i) => slots[i] = Some(value),
+                None => {
+                    return Err(LoxResult::runtime_error(
+                        &name,
+                        &format!("{self} has no parameter named '{}'.", name.as_string()),
+                    ));
+                }
+            }
+        }
+
+        // Missing arguments take their defaults, evaluated now in the
+        // function's closure. Extra ones are collected into the rest list.
         let mut e = Environment::new_with_enclosing(Rc::clone(&self.closure));
+        for ((param, slot), default) in self.params.iter().zip(slots).zip(self.defaults.iter()) {
+            let value = match (slot, default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => interpreter.evaluate_in(default.clone(), &self.closure)?,
+                (None, None) => {
+                    return Err(LoxResult::system_error(&format!(
+                        "Missing argument for parameter '{}'.",
+                        param.as_string()
+                    )));
+                }
+            };
+            e.define(&param.as_string(), value);
+        }
+        if let Some(rest) = &self.rest {
+            e.define(&rest.as_string(), LiteralValue::List(Rc::new(LoxList::new(extra))));
+        }
 
-        for (param, arg) in self.params.iter().zip(arguments.iter()) {
-            e.define(&param.as_string(), arg.clone());
+        // A generator's body doesn't run until values are asked for.
+        if self.is_generator {
+            let generator = LoxGenerator::new(&self.name, Rc::clone(&self.body), e);
+            return Ok(LiteralValue::Generator(Rc::new(generator)));
         }
 
         match interpreter.execute_block(&self.body, e) {
@@ -104,8 +188,12 @@ impl LoxCallable for LoxFunction {
         }
     }
 
-    fn arity(&self) -> usize {
-        self.params.len()
+    fn arity(&self) -> Arity {
+        let required = self.defaults.iter().take_while(|default| default.is_none()).count();
+        Arity {
+            min: required,
+            max: if self.rest.is_some() { None } else { Some(self.params.len()) },
+        }
     }
 }
 
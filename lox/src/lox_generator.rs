@@ -0,0 +1,177 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::callable::*;
+use crate::entities::*;
+use crate::environment::*;
+use crate::errors::*;
+use crate::interpreter::*;
+use crate::lox_class::*;
+use crate::lox_iterator::*;
+use crate::native_functions::*;
+use crate::stmt::*;
+
+// Where a suspended generator stopped, one frame per statement between its
+// body and the yield. The interpreter records them, innermost first, as a
+// yield unwinds, and each statement takes its own frame back on resume
+// instead of starting afresh.
+pub enum Frame {
+    Block { environment: Rc<RefCell<Environment>>, index: usize },
+    If { then_branch: bool },
+    While,
+    ForIn(LoxIterator),
+    Try(TryStage),
+    Yield,
+}
+
+// A try can yield from its body, its catch or its finally block. Finally
+// keeps the result it has to produce once it completes.
+pub enum TryStage {
+    Body,
+    Catch,
+    Finally(Result<(), LoxResult>),
+}
+
+enum GeneratorState {
+    Ready(Environment),
+    Suspended(Vec<Frame>),
+    Running,
+    Done,
+}
+
+// The object returned by calling a function that contains a yield. Its body
+// only runs as values are asked for, up to the next yield each time.
+pub struct LoxGenerator {
+    name: String,
+    body: Rc<Vec<Rc<Stmt>>>,
+    state: RefCell<GeneratorState>,
+    peeked: RefCell<Option<LiteralValue>>,
+}
+
+impl fmt::Debug for LoxGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl PartialEq for LoxGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl LoxGenerator {
+    pub fn new(name: &Token, body: Rc<Vec<Rc<Stmt>>>, environment: Environment) -> Self {
+        Self {
+            name: name.as_string(),
+            body,
+            state: RefCell::new(GeneratorState::Ready(environment)),
+            peeked: RefCell::new(None),
+        }
+    }
+
+    // The next yielded value, or None once the body has finished.
+    pub fn advance(&self, interpreter: &Interpreter) -> Result<Option<LiteralValue>, LoxResult> {
+        if let Some(value) = self.peeked.take() {
+            return Ok(Some(value));
+        }
+        let result = match self.state.replace(GeneratorState::Running) {
+            GeneratorState::Ready(environment) => {
+                interpreter.resume_generator(&self.body, environment, Vec::new())
+            }
+            GeneratorState::Suspended(frames) => {
+                interpreter.resume_generator(&self.body, Environment::new(), frames)
+            }
+            GeneratorState::Running => {
+                return Err(LoxResult::system_error(&format!("{self} is already running.")));
+            }
+            GeneratorState::Done => Ok(None),
+        };
+        match result {
+            Ok(Some((value, frames))) => {
+                self.state.replace(GeneratorState::Suspended(frames));
+                Ok(Some(value))
+            }
+            Ok(None) => {
+                self.state.replace(GeneratorState::Done);
+                Ok(None)
+            }
+            // A generator that throws is finished, as it is in Python.
+            Err(e) => {
+                self.state.replace(GeneratorState::Done);
+                Err(e)
+            }
+        }
+    }
+
+    // Answering means running ahead to the next yield, so the value is kept
+    // for the following advance().
+    pub fn has_next(&self, interpreter: &Interpreter) -> Result<bool, LoxResult> {
+        if self.peeked.borrow().is_none() {
+            let value = self.advance(interpreter)?;
+            self.peeked.replace(value);
+        }
+        Ok(self.peeked.borrow().is_some())
+    }
+
+    pub fn get(&self, name: &Token, this: &Rc<LoxGenerator>) -> Result<LiteralValue, LoxResult> {
+        let func: Rc<dyn LoxCallable> = match name.as_string().as_str() {
+            "next" => Rc::new(GeneratorNext { generator: Rc::clone(this) }),
+            "hasNext" => Rc::new(GeneratorHasNext { generator: Rc::clone(this) }),
+            _ => {
+                return Err(LoxResult::runtime_error(
+                    name,
+                    &format!("Undefined property '{}'.", name.as_string()),
+                ))
+            }
+        };
+        Ok(LiteralValue::Native(Rc::new(LoxNative { func })))
+    }
+}
+
+impl fmt::Display for LoxGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<generator {}>", self.name)
+    }
+}
+
+struct GeneratorNext {
+    generator: Rc<LoxGenerator>,
+}
+
+impl LoxCallable for GeneratorNext {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        self.generator.advance(interpreter)?.ok_or_else(|| {
+            LoxResult::system_error(&format!("{} is exhausted.", self.generator))
+        })
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(0)
+    }
+}
+
+struct GeneratorHasNext {
+    generator: Rc<LoxGenerator>,
+}
+
+impl LoxCallable for GeneratorHasNext {
+    fn call(
+        &self,
+        interpreter: &Interpreter,
+        _args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        Ok(LiteralValue::Bool(self.generator.has_next(interpreter)?))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(0)
+    }
+}
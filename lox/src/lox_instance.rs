@@ -1,6 +1,6 @@
 use std::{cell::RefCell, collections::{hash_map::Entry, HashMap}, rc::Rc};
 use std::fmt;
-use crate::{entities::{LiteralValue, Token}, lox_class::LoxClass, LoxResult};
+use crate::{callable::LoxCallable, entities::{LiteralValue, Token}, lox_class::LoxClass, Interpreter, LoxResult};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct LoxInstance {
@@ -16,15 +16,24 @@ impl LoxInstance {
         }
     }
 
-    pub fn get(&self, name: &Token, this: &Rc<LoxInstance>) -> Result<LiteralValue, LoxResult> {
+    pub fn get(
+        &self,
+        name: &Token,
+        this: &Rc<LoxInstance>,
+        interpreter: &Interpreter,
+    ) -> Result<LiteralValue, LoxResult> {
         if let Entry::Occupied(o) = self.fields.borrow_mut().entry(name.as_string()) {
-            Ok(o.get().clone())
-        } else if let Some(method) = self.klass.find_method(&name.as_string()) {
-            if let LiteralValue::Func(func) = method {
-                Ok(func.bind(&LiteralValue::Instance(Rc::clone(this))))
-            } else {
-                panic!("tried to bind 'this' to a non-function {method:?}");
+            return Ok(o.get().clone());
+        }
+
+        if let Some(LiteralValue::Func(getter)) = self.klass.find_getter(&name.as_string()) {
+            if let LiteralValue::Func(getter) = getter.bind(&LiteralValue::Instance(Rc::clone(this))) {
+                return getter.call(interpreter, Vec::new(), None);
             }
+        }
+
+        if let Some(method) = self.bound_method(&name.as_string(), this) {
+            Ok(method)
         } else {
             Err(LoxResult::runtime_error(
                 name,
@@ -34,9 +43,39 @@ impl LoxInstance {
     }
 
 
+    pub fn is_instance_of(&self, klass: &LoxClass) -> bool {
+        self.klass.is_subclass_of(klass)
+    }
+
+    pub fn bound_method(&self, name: &str, this: &Rc<LoxInstance>) -> Option<LiteralValue> {
+        match self.klass.find_method(name)? {
+            LiteralValue::Func(func) => Some(func.bind(&LiteralValue::Instance(Rc::clone(this)))),
+            method => panic!("tried to bind 'this' to a non-function {method:?}"),
+        }
+    }
+
     pub fn set(&self, name: &Token, value: LiteralValue) {
         self.fields.borrow_mut().insert(name.as_string(), value);
     }
+
+    // A setter takes over assignment to its property entirely; the value is
+    // only stored if the setter stores it somewhere itself.
+    pub fn assign(
+        &self,
+        name: &Token,
+        value: LiteralValue,
+        this: &Rc<LoxInstance>,
+        interpreter: &Interpreter,
+    ) -> Result<(), LoxResult> {
+        if let Some(LiteralValue::Func(setter)) = self.klass.find_setter(&name.as_string()) {
+            if let LiteralValue::Func(setter) = setter.bind(&LiteralValue::Instance(Rc::clone(this))) {
+                setter.call(interpreter, vec![value], None)?;
+            }
+        } else {
+            self.set(name, value);
+        }
+        Ok(())
+    }
 }
 
 
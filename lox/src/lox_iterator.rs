@@ -0,0 +1,99 @@
+use std::rc::Rc;
+
+use crate::entities::*;
+use crate::errors::*;
+use crate::interpreter::*;
+use crate::lox_generator::*;
+use crate::lox_list::*;
+use crate::lox_range::*;
+
+// The state of a for-in loop. Lists are walked by index so that elements
+// appended during the loop are visited; strings and maps are snapshotted
+// when the loop starts. Any other object takes part through the iterator
+// protocol: iterator() returns an object with hasNext() and next(), or a
+// generator.
+pub enum LoxIterator {
+    List { list: Rc<LoxList>, index: usize },
+    Values(std::vec::IntoIter<LiteralValue>),
+    Range { range: Rc<LoxRange>, index: usize },
+    Generator(Rc<LoxGenerator>),
+    Protocol(LiteralValue),
+}
+
+impl LoxIterator {
+    pub fn new(
+        interpreter: &Interpreter,
+        token: &Token,
+        iterable: LiteralValue,
+    ) -> Result<LoxIterator, LoxResult> {
+        match iterable {
+            LiteralValue::List(list) => Ok(LoxIterator::List { list, index: 0 }),
+            LiteralValue::Str(s) => Ok(LoxIterator::Values(
+                s.chars()
+                    .map(|c| LiteralValue::Str(c.to_string()))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )),
+            LiteralValue::Map(map) => Ok(LoxIterator::Values(map.keys().into_iter())),
+            LiteralValue::Range(range) => Ok(LoxIterator::Range { range, index: 0 }),
+            LiteralValue::Generator(generator) => Ok(LoxIterator::Generator(generator)),
+            LiteralValue::Enum(e) => Ok(LoxIterator::Values(e.variants().into_iter())),
+            _ => match interpreter.call_special(token, &iterable, "iterator", Vec::new())? {
+                Some(LiteralValue::Generator(generator)) => Ok(LoxIterator::Generator(generator)),
+                Some(iterator) => Ok(LoxIterator::Protocol(iterator)),
+                None => Err(LoxResult::runtime_error(
+                    token,
+                    &format!("Can't iterate over '{iterable}'. Only lists, maps, strings, ranges and objects with an iterator() method can be iterated."),
+                )),
+            },
+        }
+    }
+
+    pub fn next(
+        &mut self,
+        interpreter: &Interpreter,
+        token: &Token,
+    ) -> Result<Option<LiteralValue>, LoxResult> {
+        match self {
+            LoxIterator::List { list, index } => {
+                let value = list.elements.borrow().get(*index).cloned();
+                *index += 1;
+                Ok(value)
+            }
+            LoxIterator::Values(values) => Ok(values.next()),
+            LoxIterator::Range { range, index } => {
+                let value = range.get(*index);
+                *index += 1;
+                Ok(value)
+            }
+            LoxIterator::Generator(generator) => generator.advance(interpreter).map_err(|e| match e {
+                LoxResult::SystemError { message } => LoxResult::runtime_error(token, &message),
+                e => e,
+            }),
+            LoxIterator::Protocol(iterator) => {
+                let has_next = Self::protocol_call(interpreter, token, iterator, "hasNext")?;
+                if interpreter.is_truthy(&has_next) {
+                    Ok(Some(Self::protocol_call(interpreter, token, iterator, "next")?))
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    fn protocol_call(
+        interpreter: &Interpreter,
+        token: &Token,
+        iterator: &LiteralValue,
+        name: &str,
+    ) -> Result<LiteralValue, LoxResult> {
+        interpreter
+            .call_special(token, iterator, name, Vec::new())?
+            .ok_or_else(|| {
+                LoxResult::runtime_error(
+                    token,
+                    &format!("Iterator '{iterator}' has no {name}() method."),
+                )
+            })
+    }
+}
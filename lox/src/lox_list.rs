@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::ptr;
+
+use crate::entities::{LiteralValue, Token};
+use crate::errors::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoxList {
+    pub elements: RefCell<Vec<LiteralValue>>,
+}
+
+impl LoxList {
+    pub fn new(elements: Vec<LiteralValue>) -> Self {
+        Self {
+            elements: RefCell::new(elements),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.borrow().is_empty()
+    }
+
+    pub fn push(&self, value: LiteralValue) {
+        self.elements.borrow_mut().push(value);
+    }
+
+    pub fn get(&self, bracket: &Token, index: &LiteralValue) -> Result<LiteralValue, LoxResult> {
+        let i = self.checked_index(bracket, index)?;
+        Ok(self.elements.borrow()[i].clone())
+    }
+
+    pub fn set(
+        &self,
+        bracket: &Token,
+        index: &LiteralValue,
+        value: LiteralValue,
+    ) -> Result<(), LoxResult> {
+        let i = self.checked_index(bracket, index)?;
+        self.elements.borrow_mut()[i] = value;
+        Ok(())
+    }
+
+    // An index is valid when it is an integer, or a whole float, that falls
+    // inside the list.
+    fn checked_index(&self, bracket: &Token, index: &LiteralValue) -> Result<usize, LoxResult> {
+        let n = match index {
+            LiteralValue::Int(n) => *n,
+            LiteralValue::Num(n) if n.fract() == 0.0 => *n as i64,
+            LiteralValue::BigInt(n) => {
+                return Err(LoxResult::runtime_error(
+                    bracket,
+                    &format!("List index {n} out of range for length {}.", self.len()),
+                ))
+            }
+            _ => {
+                return Err(LoxResult::runtime_error(
+                    bracket,
+                    "List index must be an integer.",
+                ))
+            }
+        };
+        if n < 0 || n >= self.len() as i64 {
+            return Err(LoxResult::runtime_error(
+                bracket,
+                &format!("List index {n} out of range for length {}.", self.len()),
+            ));
+        }
+        Ok(n as usize)
+    }
+}
+
+thread_local! {
+    static PRINTING: RefCell<Vec<*const ()>> = const { RefCell::new(Vec::new()) };
+}
+
+// Marks a container as being printed for as long as the guard lives, so
+// that a container holding itself prints as `[...]` or `{...}` instead of
+// recursing forever.
+pub struct PrintGuard(*const ());
+
+impl PrintGuard {
+    // None if the container is already being printed further up.
+    pub fn enter<T>(container: &T) -> Option<PrintGuard> {
+        let container = ptr::from_ref(container).cast::<()>();
+        PRINTING.with(|printing| {
+            let mut printing = printing.borrow_mut();
+            if printing.contains(&container) {
+                None
+            } else {
+                printing.push(container);
+                Some(PrintGuard(container))
+            }
+        })
+    }
+}
+
+impl Drop for PrintGuard {
+    fn drop(&mut self) {
+        PRINTING.with(|printing| printing.borrow_mut().retain(|c| *c != self.0));
+    }
+}
+
+impl fmt::Display for LoxList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(_guard) = PrintGuard::enter(self) else {
+            return write!(f, "[...]");
+        };
+        write!(f, "[")?;
+        for (i, element) in self.elements.borrow().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", element.repr())?;
+        }
+        write!(f, "]")
+    }
+}
@@ -0,0 +1,191 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::entities::{LiteralValue, Token};
+use crate::lox_bigint::*;
+use crate::lox_decimal::*;
+use crate::lox_enum::*;
+use crate::errors::*;
+use crate::lox_list::PrintGuard;
+
+// Only values with a stable notion of equality can be used as keys. Numeric
+// keys are normalized the way `==` compares numbers: a whole float or
+// decimal is stored as the integer it equals, since `1 == 1.0` and
+// `1 == Decimal("1")`, other floats by their bits so that they can be
+// hashed, and other decimals without trailing zeros, since `0.5 == 0.50`.
+// A float and a decimal are only equal when both are whole, so they only
+// share a key then. Enum values are keys when their fields are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Int(i64),
+    BigInt(BigInt),
+    Num(u64),
+    Decimal(String),
+    Str(String),
+    Bool(bool),
+    Nil,
+    EnumValue(EnumKey),
+}
+
+// Compares like `==` does: the same variant with equal fields.
+#[derive(Debug, Clone)]
+pub struct EnumKey {
+    value: Rc<LoxEnumValue>,
+    fields: Vec<MapKey>,
+}
+
+impl PartialEq for EnumKey {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.value.variant, &other.value.variant) && self.fields == other.fields
+    }
+}
+
+impl Eq for EnumKey {}
+
+impl Hash for EnumKey {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        Rc::as_ptr(&self.value.variant).hash(hasher);
+        self.fields.hash(hasher);
+    }
+}
+
+impl MapKey {
+    pub fn from_value(value: &LiteralValue) -> Option<MapKey> {
+        match value {
+            LiteralValue::Int(n) => Some(MapKey::Int(*n)),
+            LiteralValue::BigInt(n) => Some(MapKey::BigInt(n.as_ref().clone())),
+            LiteralValue::Decimal(d) if d.is_integral() => {
+                MapKey::from_value(&LiteralValue::integer(d.to_bigint()))
+            }
+            LiteralValue::Decimal(d) => Some(MapKey::Decimal(d.normalized().to_string())),
+            LiteralValue::Num(n) if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n < i64::MAX as f64 => {
+                Some(MapKey::Int(*n as i64))
+            }
+            LiteralValue::Num(n) => Some(MapKey::Num(n.to_bits())),
+            LiteralValue::Str(s) => Some(MapKey::Str(s.clone())),
+            LiteralValue::Bool(b) => Some(MapKey::Bool(*b)),
+            LiteralValue::Nil => Some(MapKey::Nil),
+            LiteralValue::EnumValue(value) => Some(MapKey::EnumValue(EnumKey {
+                value: Rc::clone(value),
+                fields: value.values.iter().map(MapKey::from_value).collect::<Option<_>>()?,
+            })),
+            _ => None,
+        }
+    }
+
+    pub fn checked(token: &Token, value: &LiteralValue) -> Result<MapKey, LoxResult> {
+        MapKey::from_value(value).ok_or_else(|| {
+            LoxResult::runtime_error(
+                token,
+                &format!("Unhashable map key '{value}'. Keys must be numbers, strings, booleans, nil or enum values."),
+            )
+        })
+    }
+
+    pub fn to_value(&self) -> LiteralValue {
+        match self {
+            MapKey::Int(n) => LiteralValue::Int(*n),
+            MapKey::BigInt(n) => LiteralValue::BigInt(Rc::new(n.clone())),
+            MapKey::Decimal(d) => LiteralValue::Decimal(Rc::new(Decimal::parse(d).unwrap())),
+            MapKey::Num(bits) => LiteralValue::Num(f64::from_bits(*bits)),
+            MapKey::Str(s) => LiteralValue::Str(s.clone()),
+            MapKey::Bool(b) => LiteralValue::Bool(*b),
+            MapKey::Nil => LiteralValue::Nil,
+            MapKey::EnumValue(key) => LiteralValue::EnumValue(Rc::clone(&key.value)),
+        }
+    }
+}
+
+// Entries are kept in insertion order so that printing and key iteration
+// are deterministic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoxMap {
+    index: RefCell<HashMap<MapKey, usize>>,
+    entries: RefCell<Vec<(MapKey, LiteralValue)>>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        Self {
+            index: RefCell::new(HashMap::new()),
+            entries: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    pub fn contains(&self, key: &MapKey) -> bool {
+        self.index.borrow().contains_key(key)
+    }
+
+    pub fn keys(&self) -> Vec<LiteralValue> {
+        self.entries.borrow().iter().map(|(k, _)| k.to_value()).collect()
+    }
+
+    pub fn entries(&self) -> Vec<(LiteralValue, LiteralValue)> {
+        self.entries
+            .borrow()
+            .iter()
+            .map(|(k, v)| (k.to_value(), v.clone()))
+            .collect()
+    }
+
+    pub fn get(&self, bracket: &Token, key: &LiteralValue) -> Result<LiteralValue, LoxResult> {
+        let map_key = MapKey::checked(bracket, key)?;
+        if let Some(i) = self.index.borrow().get(&map_key) {
+            Ok(self.entries.borrow()[*i].1.clone())
+        } else {
+            Err(LoxResult::runtime_error(
+                bracket,
+                &format!("Undefined key '{key}'."),
+            ))
+        }
+    }
+
+    pub fn set(
+        &self,
+        bracket: &Token,
+        key: &LiteralValue,
+        value: LiteralValue,
+    ) -> Result<(), LoxResult> {
+        let map_key = MapKey::checked(bracket, key)?;
+        self.insert(map_key, value);
+        Ok(())
+    }
+
+    pub fn insert(&self, key: MapKey, value: LiteralValue) {
+        let existing = self.index.borrow().get(&key).copied();
+        if let Some(i) = existing {
+            self.entries.borrow_mut()[i].1 = value;
+        } else {
+            let mut entries = self.entries.borrow_mut();
+            self.index.borrow_mut().insert(key.clone(), entries.len());
+            entries.push((key, value));
+        }
+    }
+}
+
+impl fmt::Display for LoxMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(_guard) = PrintGuard::enter(self) else {
+            return write!(f, "{{...}}");
+        };
+        write!(f, "{{")?;
+        for (i, (key, value)) in self.entries.borrow().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", key.to_value().repr(), value.repr())?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,56 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::entities::*;
+use crate::environment::*;
+use crate::errors::*;
+
+// A loaded file. Its top-level definitions live in its own global
+// environment, and only the names it exports can be read through it.
+pub struct LoxModule {
+    name: String,
+    globals: Rc<RefCell<Environment>>,
+    exports: Vec<String>,
+}
+
+impl fmt::Debug for LoxModule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl PartialEq for LoxModule {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.globals, &other.globals)
+    }
+}
+
+impl LoxModule {
+    pub fn new(name: &str, globals: Rc<RefCell<Environment>>, exports: Vec<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            globals,
+            exports,
+        }
+    }
+
+    // Exports are read from the module's environment each time, so a
+    // module that reassigns an exported variable is seen by its importers.
+    pub fn get(&self, name: &Token) -> Result<LiteralValue, LoxResult> {
+        if self.exports.contains(&name.as_string()) {
+            self.globals.borrow().get(name)
+        } else {
+            Err(LoxResult::runtime_error(
+                name,
+                &format!("Module '{}' does not export '{}'.", self.name, name.as_string()),
+            ))
+        }
+    }
+}
+
+impl fmt::Display for LoxModule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
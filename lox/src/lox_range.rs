@@ -0,0 +1,44 @@
+use std::fmt;
+
+use crate::entities::LiteralValue;
+
+// A lazy sequence of numbers from start up to, but not including, end.
+// Elements are computed as they are needed rather than stored. A range of
+// integers keeps its bounds as integers, so that it stays exact past 2^53.
+#[derive(Debug, PartialEq)]
+pub enum LoxRange {
+    Int { start: i64, end: i64, step: i64 },
+    Float { start: f64, end: f64, step: f64 },
+}
+
+impl LoxRange {
+    pub fn get(&self, index: usize) -> Option<LiteralValue> {
+        match *self {
+            LoxRange::Int { start, end, step } => {
+                // A value that overflows is past any i64 end.
+                let value = i64::try_from(index)
+                    .ok()
+                    .and_then(|index| index.checked_mul(step))
+                    .and_then(|offset| start.checked_add(offset))?;
+                let in_range = if step > 0 { value < end } else { value > end };
+                in_range.then_some(LiteralValue::Int(value))
+            }
+            LoxRange::Float { start, end, step } => {
+                let value = start + index as f64 * step;
+                let in_range = if step > 0.0 { value < end } else { value > end };
+                in_range.then_some(LiteralValue::Num(value))
+            }
+        }
+    }
+}
+
+impl fmt::Display for LoxRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoxRange::Int { start, end, step } => write!(f, "range({start}, {end}, {step})"),
+            LoxRange::Float { start, end, step } => {
+                write!(f, "range({start:?}, {end:?}, {step:?})")
+            }
+        }
+    }
+}
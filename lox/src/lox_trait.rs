@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::entities::*;
+use crate::environment::*;
+use crate::lox_class::*;
+use crate::lox_function::*;
+use crate::stmt::*;
+
+// A trait's methods are closed over a different `super` in each class that
+// uses it, so they are kept as declarations until the trait is applied.
+pub struct LoxTrait {
+    name: String,
+    closure: Rc<RefCell<Environment>>,
+    methods: Rc<Vec<Rc<Stmt>>>,
+    required: Vec<String>,
+}
+
+impl fmt::Debug for LoxTrait {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl PartialEq for LoxTrait {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl LoxTrait {
+    pub fn new(
+        name: &str,
+        closure: &Rc<RefCell<Environment>>,
+        methods: Rc<Vec<Rc<Stmt>>>,
+        required: Vec<String>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            closure: Rc::clone(closure),
+            methods,
+            required,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // The methods a class using the trait has to provide.
+    pub fn required(&self) -> &[String] {
+        &self.required
+    }
+
+    // A class holding the trait's methods on top of `superclass`, which is
+    // what `super` refers to inside them. Without a superclass, `super` has
+    // no methods.
+    pub fn apply(&self, superclass: Option<Rc<LoxClass>>) -> Rc<LoxClass> {
+        let mut environment = Environment::new_with_enclosing(Rc::clone(&self.closure));
+        environment.define(
+            "super",
+            superclass.clone().map_or(LiteralValue::Nil, LiteralValue::Class),
+        );
+        let environment = Rc::new(RefCell::new(environment));
+
+        let mut methods = HashMap::new();
+        for method in self.methods.deref() {
+            if let Stmt::Function(func) = method.deref() {
+                let is_init = func.name.as_string() == "init";
+                let function = LiteralValue::Func(Rc::new(LoxFunction::new(func, &environment, is_init)));
+                methods.insert(func.name.as_string(), function);
+            } else {
+                panic!("non-function method in trait");
+            }
+        }
+        Rc::new(LoxClass::new(
+            &self.name,
+            superclass,
+            methods,
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+        ))
+    }
+}
+
+impl fmt::Display for LoxTrait {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<trait {}>", self.name)
+    }
+}
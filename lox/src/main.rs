@@ -5,7 +5,17 @@ mod entities;
 use std::rc::Rc;
 
 mod lox_instance;
+mod lox_bigint;
 mod lox_class;
+mod lox_decimal;
+mod lox_enum;
+mod lox_list;
+mod lox_generator;
+mod lox_iterator;
+mod lox_map;
+mod lox_module;
+mod lox_range;
+mod lox_trait;
 
 mod callable;
 mod lox_function;
@@ -20,6 +30,7 @@ mod interpreter;
 use interpreter::*;
 
 mod parser;
+mod pattern;
 use parser::*;
 
 mod scanner;
@@ -64,9 +75,10 @@ impl Lox {
 
     pub fn run_file(&self, path: &str) -> io::Result<()> {
         let buf = std::fs::read_to_string(path)?;
+        self.interpreter.set_main_file(path);
         match self.run(buf) {
             Ok(_) => std::process::exit(0),
-            Err(LoxResult::RuntimeError { .. }) => std::process::exit(70),
+            Err(LoxResult::RuntimeError { .. }) | Err(LoxResult::Throw { .. }) => std::process::exit(70),
             _ => std::process::exit(65),
         }
 
@@ -102,13 +114,17 @@ impl Lox {
 
         let resolver = Resolver::new(&self.interpreter);
         let s = Rc::new(statements);
-        resolver.resolve(&Rc::clone(&s))?;
-        if resolver.success() {
-            self.interpreter.interpret(&Rc::clone(&s))?;
-        } else {
-            std::process::exit(65);
+        let result = resolver.resolve(&Rc::clone(&s)).and_then(|_| {
+            if resolver.success() {
+                self.interpreter.interpret(&Rc::clone(&s))
+            } else {
+                std::process::exit(65);
+            }
+        });
+        if let Err(e) = &result {
+            e.report("");
         }
 
-        Ok(())
+        result
     }
 }
@@ -5,8 +5,13 @@ use std::time::SystemTime;
 use crate::callable::*;
 use crate::errors::*;
 use crate::interpreter::*;
+use crate::lox_bigint::*;
 use crate::lox_class::*;
+use crate::lox_decimal::*;
 use crate::entities::*;
+use crate::lox_list::*;
+use crate::lox_map::*;
+use crate::lox_range::*;
 
 
 #[derive(Clone)]
@@ -41,7 +46,7 @@ impl LoxCallable for NativeClock {
         _klass: Option<Rc<LoxClass>>,
     ) -> Result<LiteralValue, LoxResult> {
         match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => Ok(LiteralValue::Num(n.as_millis() as f64)),
+            Ok(n) => Ok(LiteralValue::Int(n.as_millis() as i64)),
             Err(e) => Err(LoxResult::system_error(&format!(
                 "Clock returned invalid duration: {:?}",
                 e.duration()
@@ -49,7 +54,344 @@ impl LoxCallable for NativeClock {
         }
     }
 
-    fn arity(&self) -> usize {
-        0
+    fn arity(&self) -> Arity {
+        Arity::exact(0)
+    }
+}
+
+pub struct NativeLen;
+
+impl LoxCallable for NativeLen {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        match &args[0] {
+            LiteralValue::List(l) => Ok(LiteralValue::Int(l.len() as i64)),
+            LiteralValue::Map(m) => Ok(LiteralValue::Int(m.len() as i64)),
+            LiteralValue::Str(s) => Ok(LiteralValue::Int(s.chars().count() as i64)),
+            _ => Err(LoxResult::system_error("len() expects a list, map or string.")),
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+}
+
+pub struct NativeAppend;
+
+impl LoxCallable for NativeAppend {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        if let LiteralValue::List(l) = &args[0] {
+            l.push(args[1].clone());
+            Ok(LiteralValue::Nil)
+        } else {
+            Err(LoxResult::system_error("append() expects a list."))
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+}
+
+pub struct NativeKeys;
+
+impl LoxCallable for NativeKeys {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        if let LiteralValue::Map(m) = &args[0] {
+            Ok(LiteralValue::List(Rc::new(LoxList::new(m.keys()))))
+        } else {
+            Err(LoxResult::system_error("keys() expects a map."))
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+}
+
+pub struct NativeHas;
+
+impl LoxCallable for NativeHas {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        if let LiteralValue::Map(m) = &args[0] {
+            match MapKey::from_value(&args[1]) {
+                Some(key) => Ok(LiteralValue::Bool(m.contains(&key))),
+                None => Err(LoxResult::system_error(&format!(
+                    "Unhashable map key '{}'.",
+                    args[1]
+                ))),
+            }
+        } else {
+            Err(LoxResult::system_error("has() expects a map."))
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+}
+
+pub struct NativeStr;
+
+impl LoxCallable for NativeStr {
+    fn call(
+        &self,
+        terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        Ok(LiteralValue::Str(terp.stringify(&args[0])?))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+}
+
+pub struct NativeRange;
+
+// range(end), range(start, end) or range(start, end, step), like Python.
+impl LoxCallable for NativeRange {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        // A range of integers yields integers.
+        let integers: Option<Vec<i64>> = args
+            .iter()
+            .map(|arg| match arg {
+                LiteralValue::Int(n) => Some(*n),
+                _ => None,
+            })
+            .collect();
+        if let Some(integers) = integers {
+            let (start, end, step) = match integers[..] {
+                [end] => (0, end, 1),
+                [start, end] => (start, end, 1),
+                [start, end, step] => (start, end, step),
+                _ => unreachable!("arity is checked before the call"),
+            };
+            if step == 0 {
+                return Err(LoxResult::system_error("range() step can't be zero."));
+            }
+            return Ok(LiteralValue::Range(Rc::new(LoxRange::Int { start, end, step })));
+        }
+
+        let mut numbers = Vec::new();
+        for arg in &args {
+            match arg {
+                LiteralValue::Int(n) => numbers.push(*n as f64),
+                LiteralValue::Num(n) => numbers.push(*n),
+                _ => return Err(LoxResult::system_error("range() expects numbers.")),
+            }
+        }
+        let (start, end, step) = match numbers[..] {
+            [end] => (0.0, end, 1.0),
+            [start, end] => (start, end, 1.0),
+            [start, end, step] => (start, end, step),
+            _ => unreachable!("arity is checked before the call"),
+        };
+        if step == 0.0 {
+            return Err(LoxResult::system_error("range() step can't be zero."));
+        }
+        Ok(LiteralValue::Range(Rc::new(LoxRange::Float { start, end, step })))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity { min: 1, max: Some(3) }
+    }
+}
+
+pub struct NativeInt;
+
+// Floats and decimals are truncated towards zero; strings must hold a whole
+// number.
+impl LoxCallable for NativeInt {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        match &args[0] {
+            LiteralValue::Int(_) | LiteralValue::BigInt(_) => Ok(args[0].clone()),
+            LiteralValue::Num(n) if n.is_finite() => {
+                Ok(LiteralValue::integer(BigInt::parse(&format!("{:.0}", n.trunc())).unwrap()))
+            }
+            LiteralValue::Decimal(d) => Ok(LiteralValue::integer(d.to_bigint())),
+            LiteralValue::Str(s) => BigInt::parse(s.trim()).map(LiteralValue::integer).ok_or_else(|| {
+                LoxResult::system_error(&format!("int() can't convert '{s}'."))
+            }),
+            LiteralValue::Num(n) => Err(LoxResult::system_error(&format!(
+                "int() can't convert '{}'.",
+                LiteralValue::Num(*n)
+            ))),
+            _ => Err(LoxResult::system_error("int() expects a number or string.")),
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+}
+
+pub struct NativeFloat;
+
+impl LoxCallable for NativeFloat {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        match &args[0] {
+            LiteralValue::Int(n) => Ok(LiteralValue::Num(*n as f64)),
+            LiteralValue::BigInt(n) => Ok(LiteralValue::Num(n.to_f64())),
+            LiteralValue::Num(n) => Ok(LiteralValue::Num(*n)),
+            LiteralValue::Decimal(d) => Ok(LiteralValue::Num(d.to_f64())),
+            LiteralValue::Str(s) => s.trim().parse().map(LiteralValue::Num).map_err(|_| {
+                LoxResult::system_error(&format!("float() can't convert '{s}'."))
+            }),
+            _ => Err(LoxResult::system_error("float() expects a number or string.")),
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+}
+
+pub struct NativeDecimal;
+
+// Decimal("0.1") is exactly one tenth. A float converts by the digits it
+// prints, so Decimal(0.1) is one tenth too.
+impl LoxCallable for NativeDecimal {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        let decimal = match &args[0] {
+            LiteralValue::Int(n) => Some(Decimal::from_bigint(BigInt::from_i64(*n))),
+            LiteralValue::BigInt(n) => Some(Decimal::from_bigint(n.as_ref().clone())),
+            LiteralValue::Decimal(d) => Some(d.as_ref().clone()),
+            LiteralValue::Num(n) if n.is_finite() => Decimal::parse(&n.to_string()),
+            LiteralValue::Num(_) => None,
+            LiteralValue::Str(s) => Decimal::parse(s),
+            _ => return Err(LoxResult::system_error("Decimal() expects a number or string.")),
+        };
+        decimal
+            .map(|d| LiteralValue::Decimal(Rc::new(d)))
+            .ok_or_else(|| LoxResult::system_error(&format!("Decimal() can't convert '{}'.", args[0])))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+}
+
+fn rounding_mode(name: &LiteralValue) -> Result<Rounding, LoxResult> {
+    match name {
+        LiteralValue::Str(s) => Rounding::parse(s).ok_or_else(|| {
+            LoxResult::system_error(&format!(
+                "Unknown rounding mode '{s}'. Expected half_even, half_up, half_down, up, down, ceiling or floor."
+            ))
+        }),
+        _ => Err(LoxResult::system_error("Rounding mode must be a string.")),
+    }
+}
+
+fn decimal_places(places: &LiteralValue) -> Result<u32, LoxResult> {
+    match places {
+        LiteralValue::Int(n) if (0..=MAX_PLACES as i64).contains(n) => Ok(*n as u32),
+        _ => Err(LoxResult::system_error(&format!(
+            "Decimal places must be an integer from 0 to {MAX_PLACES}."
+        ))),
+    }
+}
+
+pub struct NativeDecimalContext;
+
+// decimalContext(places) or decimalContext(places, rounding) sets how decimal
+// division rounds a quotient that doesn't end.
+impl LoxCallable for NativeDecimalContext {
+    fn call(
+        &self,
+        terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        let mut context = terp.decimal_context();
+        context.places = decimal_places(&args[0])?;
+        if let Some(rounding) = args.get(1) {
+            context.rounding = rounding_mode(rounding)?;
+        }
+        terp.set_decimal_context(context);
+        Ok(LiteralValue::Nil)
+    }
+
+    fn arity(&self) -> Arity {
+        Arity { min: 1, max: Some(2) }
+    }
+}
+
+// Decimals have a round(places) method, rounding with the context's mode
+// unless given another.
+pub fn decimal_property(decimal: &Rc<Decimal>, name: &Token) -> Result<LiteralValue, LoxResult> {
+    match name.as_string().as_str() {
+        "round" => Ok(LiteralValue::Native(Rc::new(LoxNative {
+            func: Rc::new(DecimalRound { decimal: Rc::clone(decimal) }),
+        }))),
+        _ => Err(LoxResult::runtime_error(
+            name,
+            &format!("Undefined property '{}'.", name.as_string()),
+        )),
+    }
+}
+
+struct DecimalRound {
+    decimal: Rc<Decimal>,
+}
+
+impl LoxCallable for DecimalRound {
+    fn call(
+        &self,
+        terp: &Interpreter,
+        args: Vec<LiteralValue>,
+        _klass: Option<Rc<LoxClass>>,
+    ) -> Result<LiteralValue, LoxResult> {
+        let places = decimal_places(&args[0])?;
+        let rounding = match args.get(1) {
+            Some(rounding) => rounding_mode(rounding)?,
+            None => terp.decimal_context().rounding,
+        };
+        Ok(LiteralValue::Decimal(Rc::new(self.decimal.round(places, rounding))))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity { min: 1, max: Some(2) }
     }
 }
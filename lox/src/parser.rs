@@ -1,8 +1,10 @@
+use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::entities::*;
 use crate::errors::*;
 use crate::expr::*;
+use crate::pattern::*;
 use crate::stmt::*;
 
 pub struct Parser<'a> {
@@ -11,8 +13,51 @@ pub struct Parser<'a> {
     had_error: bool,
 }
 
+// A parameter list: the named parameters, the default value of each one
+// that has one, and an optional trailing `...rest` parameter.
+struct Parameters {
+    params: Vec<Token>,
+    defaults: Vec<Option<Rc<Expr>>>,
+    rest: Option<Token>,
+}
+
+impl Parameters {
+    fn into_function(self, name: Token, body: Vec<Rc<Stmt>>) -> FunctionStmt {
+        FunctionStmt {
+            name,
+            params: Rc::new(self.params),
+            defaults: Rc::new(self.defaults),
+            rest: self.rest,
+            is_generator: contains_yield(&body),
+            body: Rc::new(body),
+        }
+    }
+}
+
+// A function is a generator when a yield appears in its own body. Nested
+// functions are generators or not in their own right, and the resolver
+// rejects a yield inside a match, so only statements need searching.
+fn contains_yield(statements: &[Rc<Stmt>]) -> bool {
+    statements.iter().any(|statement| match statement.deref() {
+        Stmt::Yield(_) => true,
+        Stmt::Block(block) => contains_yield(&block.statements),
+        Stmt::If(stmt) => {
+            contains_yield(std::slice::from_ref(&stmt.then_branch))
+                || stmt.else_branch.iter().any(|branch| contains_yield(std::slice::from_ref(branch)))
+        }
+        Stmt::While(stmt) => contains_yield(std::slice::from_ref(&stmt.body)),
+        Stmt::ForIn(stmt) => contains_yield(std::slice::from_ref(&stmt.body)),
+        Stmt::Try(stmt) => {
+            contains_yield(&stmt.body)
+                || stmt.catch_body.iter().any(|body| contains_yield(body))
+                || stmt.finally_body.iter().any(|body| contains_yield(body))
+        }
+        _ => false,
+    })
+}
+
 impl<'a> Parser<'a> {
-    pub fn new(tokens: &[Token]) -> Parser {
+    pub fn new(tokens: &[Token]) -> Parser<'_> {
         Parser {
             tokens,
             current: 0,
@@ -41,10 +86,23 @@ impl<'a> Parser<'a> {
     fn declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
         let result = if self.is_match(&[TokenType::CLASS])  {
             self.class_declaration()
-        } else if self.is_match(&[TokenType::FUN]) {
+        } else if self.is_match(&[TokenType::ENUM]) {
+            self.enum_declaration()
+        } else if self.is_match(&[TokenType::TRAIT]) {
+            self.trait_declaration()
+        } else if self.check(TokenType::FUN) && self.check_next(TokenType::IDENTIFIER) {
+            self.advance();
             self.function("function")
         } else if self.is_match(&[TokenType::VAR]) {
             self.var_declaration()
+        } else if self.is_match(&[TokenType::CONST]) {
+            self.const_declaration()
+        } else if self.is_match(&[TokenType::EXPORT]) {
+            self.export_declaration()
+        } else if self.is_match(&[TokenType::IMPORT]) {
+            self.import_statement()
+        } else if self.is_match(&[TokenType::FROM]) {
+            self.selective_import_statement()
         } else {
             self.statement()
         };
@@ -55,6 +113,136 @@ impl<'a> Parser<'a> {
         result
     }
 
+    fn export_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let keyword = self.previous().dup();
+        let declaration = if self.is_match(&[TokenType::CLASS]) {
+            self.class_declaration()?
+        } else if self.is_match(&[TokenType::ENUM]) {
+            self.enum_declaration()?
+        } else if self.is_match(&[TokenType::TRAIT]) {
+            self.trait_declaration()?
+        } else if self.check(TokenType::FUN) && self.check_next(TokenType::IDENTIFIER) {
+            self.advance();
+            self.function("function")?
+        } else if self.is_match(&[TokenType::VAR]) {
+            self.var_declaration()?
+        } else if self.is_match(&[TokenType::CONST]) {
+            self.const_declaration()?
+        } else {
+            let peek = self.peek().dup();
+            return Err(self.error(&peek, "Expect class, enum, trait, function or variable declaration after 'export'."));
+        };
+        Ok(Rc::new(Stmt::Export(Rc::new(ExportStmt { keyword, declaration }))))
+    }
+
+    // import "path/to/module.lox" as name;
+    fn import_statement(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let keyword = self.previous().dup();
+        let path = self.consume(TokenType::STRING, "Expect module path after 'import'.")?;
+        self.consume(TokenType::AS, "Expect 'as' after module path.")?;
+        let alias = self.consume(TokenType::IDENTIFIER, "Expect module name after 'as'.")?;
+        self.consume(TokenType::SEMICOLON, "Expect ';' after import.")?;
+        Ok(Rc::new(Stmt::Import(Rc::new(ImportStmt {
+            keyword,
+            path,
+            alias: Some(alias),
+            names: Rc::new(Vec::new()),
+        }))))
+    }
+
+    // from "path/to/module.lox" import a, b;
+    fn selective_import_statement(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let keyword = self.previous().dup();
+        let path = self.consume(TokenType::STRING, "Expect module path after 'from'.")?;
+        self.consume(TokenType::IMPORT, "Expect 'import' after module path.")?;
+        let mut names = vec![self.consume(TokenType::IDENTIFIER, "Expect name to import.")?];
+        while self.is_match(&[TokenType::COMMA]) {
+            names.push(self.consume(TokenType::IDENTIFIER, "Expect name to import.")?);
+        }
+        self.consume(TokenType::SEMICOLON, "Expect ';' after import.")?;
+        Ok(Rc::new(Stmt::Import(Rc::new(ImportStmt {
+            keyword,
+            path,
+            alias: None,
+            names: Rc::new(names),
+        }))))
+    }
+
+    // enum Name { A, B(field, ...), ... } with an optional trailing comma.
+    fn enum_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let name = self.consume(TokenType::IDENTIFIER, "Expect enum name.")?;
+        self.consume(TokenType::LEFT_BRACE, "Expect '{' before enum body.")?;
+
+        let mut variants: Vec<Token> = Vec::new();
+        let mut fields = Vec::new();
+        while !self.check(TokenType::RIGHT_BRACE) && !self.is_at_end() {
+            let variant = self.consume(TokenType::IDENTIFIER, "Expect variant name.")?;
+            if variants.iter().any(|other| other.as_string() == variant.as_string()) {
+                self.error(&variant, &format!("Duplicate variant '{}' in enum.", variant.as_string()));
+            }
+            let variant_fields = if self.is_match(&[TokenType::LEFT_PAREN]) {
+                let mut names: Vec<Token> = Vec::new();
+                if !self.check(TokenType::RIGHT_PAREN) {
+                    loop {
+                        let field = self.consume(TokenType::IDENTIFIER, "Expect field name.")?;
+                        if names.iter().any(|other| other.as_string() == field.as_string()) {
+                            self.error(&field, &format!("Duplicate field '{}' in variant.", field.as_string()));
+                        }
+                        names.push(field);
+                        if !self.is_match(&[TokenType::COMMA]) {
+                            break;
+                        }
+                    }
+                }
+                self.consume(TokenType::RIGHT_PAREN, "Expect ')' after variant fields.")?;
+                Some(names)
+            } else {
+                None
+            };
+            variants.push(variant);
+            fields.push(variant_fields);
+            if !self.is_match(&[TokenType::COMMA]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RIGHT_BRACE, "Expect '}' after enum body.")?;
+
+        Ok(Rc::new(Stmt::Enum(Rc::new(EnumStmt {
+            name,
+            variants: Rc::new(variants),
+            fields: Rc::new(fields),
+        }))))
+    }
+
+    // A method declared without a body, as in `area();`, is one a class
+    // using the trait must provide.
+    fn trait_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let name = self.consume(TokenType::IDENTIFIER, "Expect trait name.")?;
+        self.consume(TokenType::LEFT_BRACE, "Expect '{' before trait body.")?;
+
+        let mut methods = Vec::new();
+        let mut required = Vec::new();
+        while !self.check(TokenType::RIGHT_BRACE) && !self.is_at_end() {
+            let method = self.consume(TokenType::IDENTIFIER, "Expect method name.")?;
+            self.consume(TokenType::LEFT_PAREN, "Expect '(' after method name.")?;
+            let parameters = self.parameters()?;
+            if self.is_match(&[TokenType::SEMICOLON]) {
+                required.push(method);
+            } else {
+                self.consume(TokenType::LEFT_BRACE, "Expect '{' or ';' after trait method parameters.")?;
+                let body = self.block()?;
+                methods.push(Rc::new(Stmt::Function(Rc::new(parameters.into_function(method, body)))));
+            }
+        }
+        self.consume(TokenType::RIGHT_BRACE, "Expect '}' after trait body.")?;
+
+        Ok(Rc::new(Stmt::Trait(Rc::new(TraitStmt {
+            name,
+            methods: Rc::new(methods),
+            required: Rc::new(required),
+        }))))
+    }
+
     fn class_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
         let name = self.consume(TokenType::IDENTIFIER, "Expect class name")?;
 
@@ -66,11 +254,41 @@ impl<'a> Parser<'a> {
         } else {
             None
         };
+
+        let mut traits = Vec::new();
+        if self.is_match(&[TokenType::WITH]) {
+            loop {
+                self.consume(TokenType::IDENTIFIER, "Expect trait name.")?;
+                traits.push(Rc::new(Expr::Variable(Rc::new(VariableExpr {
+                    name: self.previous().dup(),
+                }))));
+                if !self.is_match(&[TokenType::COMMA]) {
+                    break;
+                }
+            }
+        }
         self.consume(TokenType::LEFT_BRACE, "Expect '{' before class body")?;
 
         let mut methods = Vec::new();
+        let mut static_methods = Vec::new();
+        let mut static_fields = Vec::new();
+        let mut getters = Vec::new();
+        let mut setters = Vec::new();
         while !self.check(TokenType::RIGHT_BRACE) && !self.is_at_end() {
-            methods.push(self.function("method")?);
+            if self.is_match(&[TokenType::STATIC, TokenType::CLASS]) {
+                if self.check_next(TokenType::LEFT_PAREN) {
+                    static_methods.push(self.function("method")?);
+                } else {
+                    static_fields.push(self.var_declaration()?);
+                }
+            } else if self.check_next(TokenType::LEFT_BRACE) {
+                getters.push(self.getter()?);
+            } else if self.peek().as_string() == "set" && self.check_next(TokenType::IDENTIFIER) {
+                self.advance();
+                setters.push(self.setter()?);
+            } else {
+                methods.push(self.function("method")?);
+            }
         }
 
         self.consume(TokenType::RIGHT_BRACE, "Expect '}' after class body")?;
@@ -78,16 +296,57 @@ impl<'a> Parser<'a> {
         Ok(Rc::new(Stmt::Class(Rc::new(ClassStmt {
             name, 
             superclass,
+            traits: Rc::new(traits),
             methods: Rc::new(methods),
+            static_methods: Rc::new(static_methods),
+            static_fields: Rc::new(static_fields),
+            getters: Rc::new(getters),
+            setters: Rc::new(setters),
         }))))
     }
 
+    // A getter is a method declared without a parameter list. It runs
+    // whenever the property is read.
+    fn getter(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let name = self.consume(TokenType::IDENTIFIER, "Expect getter name")?;
+        self.consume(TokenType::LEFT_BRACE, "Expect '{' before getter body")?;
+        let body = self.block()?;
+        let parameters = Parameters {
+            params: Vec::new(),
+            defaults: Vec::new(),
+            rest: None,
+        };
+        Ok(Rc::new(Stmt::Function(Rc::new(parameters.into_function(name, body)))))
+    }
+
+    fn setter(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let setter = self.function("setter")?;
+        if let Stmt::Function(func) = setter.deref() {
+            if func.params.len() != 1 || func.rest.is_some() {
+                let name = func.name.dup();
+                return Err(self.error(&name, "A setter must take exactly one parameter."));
+            }
+        }
+        Ok(setter)
+    }
+
     fn statement(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        // A match used as a statement doesn't need a trailing ';'.
+        if self.is_match(&[TokenType::MATCH]) {
+            let expression = Rc::new(self.match_expression()?);
+            self.is_match(&[TokenType::SEMICOLON]);
+            return Ok(Rc::new(Stmt::Expression(Rc::new(ExpressionStmt { expression }))));
+        }
         if self.is_match(&[TokenType::Break]) {
             let token = self.previous().dup();
             self.consume(TokenType::SEMICOLON, "Expect ';' after break statement.")?;
             return Ok(Rc::new(Stmt::Break(Rc::new(BreakStmt { token }))));
         }
+        if self.is_match(&[TokenType::Continue]) {
+            let token = self.previous().dup();
+            self.consume(TokenType::SEMICOLON, "Expect ';' after continue statement.")?;
+            return Ok(Rc::new(Stmt::Continue(Rc::new(ContinueStmt { token }))));
+        }
         if self.is_match(&[TokenType::FOR]) {
             return self.for_statement();
         }
@@ -107,6 +366,28 @@ impl<'a> Parser<'a> {
             return Ok(Rc::new(self.while_statement()?));
         }
 
+        if self.is_match(&[TokenType::YIELD]) {
+            let keyword = self.previous().dup();
+            let value = if self.check(TokenType::SEMICOLON) {
+                None
+            } else {
+                Some(Rc::new(self.expression()?))
+            };
+            self.consume(TokenType::SEMICOLON, "Expect ';' after yielded value.")?;
+            return Ok(Rc::new(Stmt::Yield(Rc::new(YieldStmt { keyword, value }))));
+        }
+
+        if self.is_match(&[TokenType::THROW]) {
+            let keyword = self.previous().dup();
+            let value = Rc::new(self.expression()?);
+            self.consume(TokenType::SEMICOLON, "Expect ';' after thrown value.")?;
+            return Ok(Rc::new(Stmt::Throw(Rc::new(ThrowStmt { keyword, value }))));
+        }
+
+        if self.is_match(&[TokenType::TRY]) {
+            return Ok(Rc::new(self.try_statement()?));
+        }
+
         if self.is_match(&[TokenType::LEFT_BRACE]) {
             return Ok(Rc::new(Stmt::Block(Rc::new(BlockStmt {
                 statements: Rc::new(self.block()?),
@@ -117,6 +398,9 @@ impl<'a> Parser<'a> {
     }
     fn for_statement(&mut self) -> Result<Rc<Stmt>, LoxResult> {
         self.consume(TokenType::LEFT_PAREN, "Expect '(' after 'for'.")?;
+        if self.check(TokenType::IDENTIFIER) && self.check_next(TokenType::IN) {
+            return self.for_in_statement();
+        }
 
         let initializer = if self.is_match(&[TokenType::SEMICOLON]) {
             None
@@ -142,22 +426,11 @@ impl<'a> Parser<'a> {
 
         self.consume(TokenType::RIGHT_PAREN, "Expect ')' after for clauses.")?;
 
-        let mut body = self.statement()?;
+        let body = self.statement()?;
 
-        if let Some(incr) = increment {
-            body = Rc::new(Stmt::Block(Rc::new(BlockStmt {
-                statements: Rc::new(vec![
-                    body,
-                    Rc::new(Stmt::Expression(Rc::new(ExpressionStmt {
-                        expression: Rc::new(incr),
-            }))),
-        ]),
-    })));
-} 
-        
-
-
-        body = Rc::new(Stmt::While(Rc::new(WhileStmt {
+        // The increment is kept on the while loop rather than appended to the
+        // body so that `continue` still runs it before the next iteration.
+        let mut body = Rc::new(Stmt::While(Rc::new(WhileStmt {
             condition: if let Some(cond) = condition {
                 Rc::new(cond)
             } else {
@@ -166,6 +439,7 @@ impl<'a> Parser<'a> {
                 })))
             },
             body,
+            increment: increment.map(Rc::new),
         })));
 
         if let Some(init) = initializer {
@@ -176,6 +450,55 @@ impl<'a> Parser<'a> {
 
         Ok(body)
     }
+    // for (name in iterable) body
+    fn for_in_statement(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let name = self.advance().dup();
+        let keyword = self.advance().dup();
+        let iterable = Rc::new(self.expression()?);
+        self.consume(TokenType::RIGHT_PAREN, "Expect ')' after for-in clause.")?;
+        let body = self.statement()?;
+        Ok(Rc::new(Stmt::ForIn(Rc::new(ForInStmt {
+            name,
+            keyword,
+            iterable,
+            body,
+        }))))
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let keyword = self.previous().dup();
+        self.consume(TokenType::LEFT_BRACE, "Expect '{' after 'try'.")?;
+        let body = Rc::new(self.block()?);
+
+        let (catch_name, catch_body) = if self.is_match(&[TokenType::CATCH]) {
+            self.consume(TokenType::LEFT_PAREN, "Expect '(' after 'catch'.")?;
+            let name = self.consume(TokenType::IDENTIFIER, "Expect exception variable name.")?;
+            self.consume(TokenType::RIGHT_PAREN, "Expect ')' after exception variable.")?;
+            self.consume(TokenType::LEFT_BRACE, "Expect '{' before catch body.")?;
+            (Some(name), Some(Rc::new(self.block()?)))
+        } else {
+            (None, None)
+        };
+
+        let finally_body = if self.is_match(&[TokenType::FINALLY]) {
+            self.consume(TokenType::LEFT_BRACE, "Expect '{' after 'finally'.")?;
+            Some(Rc::new(self.block()?))
+        } else {
+            None
+        };
+
+        if catch_body.is_none() && finally_body.is_none() {
+            return Err(self.error(&keyword, "Expect 'catch' or 'finally' after try block."));
+        }
+
+        Ok(Stmt::Try(Rc::new(TryStmt {
+            body,
+            catch_name,
+            catch_body,
+            finally_body,
+        })))
+    }
+
     fn if_statement(&mut self) -> Result<Stmt, LoxResult> {
         let _ = self.consume(TokenType::LEFT_PAREN, "Expect '(' after 'if'.");
         let condition = Rc::new(self.expression()?);
@@ -216,6 +539,9 @@ impl<'a> Parser<'a> {
     }
 
     fn var_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        if self.is_match(&[TokenType::LEFT_BRACKET, TokenType::LEFT_BRACE]) {
+            return self.destructuring_declaration();
+        }
         let name = self.consume(TokenType::IDENTIFIER, "Expect variable name")?;
 
         let initializer = if self.is_match(&[TokenType::EQUAL]) {
@@ -231,48 +557,202 @@ impl<'a> Parser<'a> {
         Ok(Rc::new(Stmt::Var(Rc::new(VarStmt { name, initializer }))))
     }
 
+    fn const_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let name = self.consume(TokenType::IDENTIFIER, "Expect constant name.")?;
+        self.consume(TokenType::EQUAL, "Constant must be initialized.")?;
+        let initializer = Rc::new(self.expression()?);
+        self.consume(
+            TokenType::SEMICOLON,
+            "Expect ';' after constant declaration.",
+        )?;
+        Ok(Rc::new(Stmt::Const(Rc::new(ConstStmt { name, initializer }))))
+    }
+
     fn while_statement(&mut self) -> Result<Stmt, LoxResult> {
         self.consume(TokenType::LEFT_PAREN, "Expect '(' after 'while'.")?;
         let condition = Rc::new(self.expression()?);
         self.consume(TokenType::RIGHT_PAREN, "Expect ')' after 'while'.")?;
         let body = self.statement()?;
-        Ok(Stmt::While(Rc::new(WhileStmt { condition, body })))
+        Ok(Stmt::While(Rc::new(WhileStmt {
+            condition,
+            body,
+            increment: None,
+        })))
+    }
+
+    // var [a, b] = list; reads elements by position, and
+    // var {x, y} = object; reads properties or map keys by name.
+    fn destructuring_declaration(&mut self) -> Result<Rc<Stmt>, LoxResult> {
+        let pattern = self.previous().dup();
+        let close = if pattern.is(TokenType::LEFT_BRACKET) {
+            TokenType::RIGHT_BRACKET
+        } else {
+            TokenType::RIGHT_BRACE
+        };
+
+        let mut names = Vec::new();
+        if !self.check(close) {
+            names.push(self.consume(TokenType::IDENTIFIER, "Expect variable name")?);
+            while self.is_match(&[TokenType::COMMA]) {
+                names.push(self.consume(TokenType::IDENTIFIER, "Expect variable name")?);
+            }
+        }
+        self.consume(close, "Expect end of destructuring pattern.")?;
+        self.consume(TokenType::EQUAL, "Destructuring declaration needs an initializer.")?;
+        let initializer = Rc::new(self.expression()?);
+        self.consume(
+            TokenType::SEMICOLON,
+            "Expect ';' after variable declaration.",
+        )?;
+        Ok(Rc::new(Stmt::Destructure(Rc::new(DestructureStmt {
+            pattern,
+            names,
+            initializer,
+        }))))
     }
 
     fn expression_statement(&mut self) -> Result<Rc<Stmt>, LoxResult> {
         let expr = Rc::new(self.expression()?);
+        if self.check(TokenType::COMMA) {
+            return self.multiple_assignment(expr);
+        }
         self.consume(TokenType::SEMICOLON, "Expect ';' after expression.")?;
         Ok(Rc::new(Stmt::Expression(Rc::new(ExpressionStmt {
             expression: expr,
         }))))
     }
 
+    // a, b = b, a; evaluates every value before assigning any target. A
+    // single value on the right is unpacked as a list.
+    fn multiple_assignment(&mut self, first: Rc<Expr>) -> Result<Rc<Stmt>, LoxResult> {
+        let mut targets = vec![first];
+        while self.is_match(&[TokenType::COMMA]) {
+            targets.push(Rc::new(self.conditional()?));
+        }
+        let equals = self.consume(TokenType::EQUAL, "Expect '=' after assignment targets.")?;
+        if !targets.iter().all(|target| Self::is_assignable(target)) {
+            return Err(self.error(&equals, "Invalid assignment target."));
+        }
+
+        let mut values = vec![Rc::new(self.expression()?)];
+        while self.is_match(&[TokenType::COMMA]) {
+            values.push(Rc::new(self.expression()?));
+        }
+        if values.len() != 1 && values.len() != targets.len() {
+            let message = format!("Expected {} values but got {}.", targets.len(), values.len());
+            return Err(self.error(&equals, &message));
+        }
+        self.consume(TokenType::SEMICOLON, "Expect ';' after assignment.")?;
+
+        Ok(Rc::new(Stmt::MultiAssign(Rc::new(MultiAssignStmt {
+            targets,
+            equals,
+            values,
+        }))))
+    }
+
     fn function(&mut self, kind: &str) -> Result<Rc<Stmt>, LoxResult> {
         let name = self.consume(TokenType::IDENTIFIER, &format!("Expect {kind} name"))?;
 
         self.consume(TokenType::LEFT_PAREN, &format!("Expect '(' after {kind} name"))?;
-        
+        let parameters = self.parameters()?;
+
+        self.consume(TokenType::LEFT_BRACE, &format!("Expect '{{' before  {kind} body"))?;
+        let body = self.block()?;
+        Ok(Rc::new(Stmt::Function(Rc::new(parameters.into_function(name, body)))))
+    }
+
+    // Once a parameter has a default, the ones after it need defaults too,
+    // and a `...rest` parameter must come last.
+    fn parameters(&mut self) -> Result<Parameters, LoxResult> {
         let mut params = Vec::new();
+        let mut defaults = Vec::new();
+        let mut rest = None;
         if !self.check(TokenType::RIGHT_PAREN) {
-            params.push(self.consume(TokenType::IDENTIFIER, "Expect parameter name")?);
-            while self.is_match(&[TokenType::COMMA]) {
+            loop {
                 if params.len() >= 255 && !self.had_error {
                     let peek = self.peek().dup();
                     self.error(&peek, "Can't have more than 255 parameters.");
                 }
-                params.push(self.consume(TokenType::IDENTIFIER, "Expect parameter name")?);   
+                if self.is_match(&[TokenType::DOT_DOT_DOT]) {
+                    rest = Some(self.consume(TokenType::IDENTIFIER, "Expect parameter name after '...'.")?);
+                    break;
+                }
+                let param = self.consume(TokenType::IDENTIFIER, "Expect parameter name")?;
+                if self.is_match(&[TokenType::EQUAL]) {
+                    defaults.push(Some(Rc::new(self.expression()?)));
+                } else if defaults.iter().any(Option::is_some) {
+                    return Err(self.error(&param, "A parameter without a default can't follow one with a default."));
+                } else {
+                    defaults.push(None);
+                }
+                params.push(param);
+                if !self.is_match(&[TokenType::COMMA]) {
+                    break;
+                }
             }
         }
 
         self.consume(TokenType::RIGHT_PAREN, "Expect ')' after parameter name")?;
+        Ok(Parameters { params, defaults, rest })
+    }
 
-        self.consume(TokenType::LEFT_BRACE, &format!("Expect '{{' before  {kind} body"))?;
-        let body = Rc::new(self.block()?);
-        Ok(Rc::new(Stmt::Function(Rc::new(FunctionStmt { 
-            name, 
-            params: Rc::new(params), 
-            body, 
-        }))))
+    // Anonymous functions get a synthetic name so they print as
+    // `<fn anonymous>` and otherwise behave like a declared function.
+    fn lambda(&mut self) -> Result<Expr, LoxResult> {
+        let keyword = self.previous().dup();
+        let name = Token::new(TokenType::IDENTIFIER, "anonymous".to_string(), None, keyword.line);
+        self.consume(TokenType::LEFT_PAREN, "Expect '(' after 'fun'.")?;
+        let parameters = self.parameters()?;
+
+        self.consume(TokenType::LEFT_BRACE, "Expect '{' before function body.")?;
+        let body = self.block()?;
+        Ok(Expr::Lambda(Rc::new(LambdaExpr {
+            declaration: Rc::new(parameters.into_function(name, body)),
+        })))
+    }
+
+    // `(a, b) => expr` is shorthand for `fun (a, b) { return expr; }`.
+    fn arrow_function(&mut self) -> Result<Expr, LoxResult> {
+        let paren = self.previous().dup();
+        let name = Token::new(TokenType::IDENTIFIER, "anonymous".to_string(), None, paren.line);
+        let parameters = self.parameters()?;
+        let arrow = self.consume(TokenType::ARROW, "Expect '=>' after parameters.")?;
+
+        let body = if self.is_match(&[TokenType::LEFT_BRACE]) {
+            self.block()?
+        } else {
+            let value = self.expression()?;
+            vec![Rc::new(Stmt::Return(Rc::new(ReturnStmt {
+                keyword: arrow,
+                value: Some(Rc::new(value)),
+            })))]
+        };
+        Ok(Expr::Lambda(Rc::new(LambdaExpr {
+            declaration: Rc::new(parameters.into_function(name, body)),
+        })))
+    }
+
+    // Looks past a '(' for its matching ')' and checks whether '=>' follows,
+    // without consuming anything. Parameter defaults can contain parentheses
+    // of their own, so this has to track nesting.
+    fn is_arrow_function(&self) -> bool {
+        let mut i = self.current + 1;
+        let mut depth = 1;
+        while i < self.tokens.len() {
+            if self.token_is(i, TokenType::LEFT_PAREN) {
+                depth += 1;
+            } else if self.token_is(i, TokenType::RIGHT_PAREN) {
+                depth -= 1;
+                if depth == 0 {
+                    return self.token_is(i + 1, TokenType::ARROW);
+                }
+            } else if self.token_is(i, TokenType::EOF) {
+                return false;
+            }
+            i += 1;
+        }
+        false
     }
 
     fn block(&mut self) -> Result<Vec<Rc<Stmt>>, LoxResult> {
@@ -303,7 +783,7 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
     fn assignment(&mut self) -> Result<Expr, LoxResult> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
 
         if self.is_match(&[TokenType::EQUAL]) {
             let equals = self.previous().dup();
@@ -320,9 +800,203 @@ impl<'a> Parser<'a> {
                     name: get.name.dup(),
                     value: Rc::new(value),
                 })))
+            } else if let Expr::Index(index) = expr {
+                return Ok(Expr::IndexSet(Rc::new(IndexSetExpr {
+                    literalvalue: Rc::clone(&index.literalvalue),
+                    bracket: index.bracket.dup(),
+                    index: Rc::clone(&index.index),
+                    value: Rc::new(value),
+                })));
             }
 
             self.error(&equals, "Invalid assignment target.");
+        } else if self.is_match(&[
+            TokenType::PLUS_EQUAL,
+            TokenType::MINUS_EQUAL,
+            TokenType::STAR_EQUAL,
+            TokenType::SLASH_EQUAL,
+            TokenType::PERCENT_EQUAL,
+        ]) {
+            let operator = self.previous().dup();
+            let value = self.assignment()?;
+
+            if Parser::is_assignable(&expr) {
+                return Ok(Expr::Compound(Rc::new(CompoundExpr {
+                    target: Rc::new(expr),
+                    operator: Parser::arithmetic_operator(&operator),
+                    value: Rc::new(value),
+                })));
+            }
+
+            self.error(&operator, "Invalid assignment target.");
+        }
+
+        Ok(expr)
+    }
+
+    fn is_assignable(expr: &Expr) -> bool {
+        matches!(expr, Expr::Variable(_) | Expr::Get(_) | Expr::Index(_))
+    }
+
+    // Compound operators keep their lexeme for error messages but carry the
+    // token type of the arithmetic they perform, so `+=` and `++` act as `+`.
+    fn arithmetic_operator(operator: &Token) -> Token {
+        let ttype = match operator.token_type() {
+            TokenType::PLUS_EQUAL | TokenType::PLUS_PLUS => TokenType::PLUS,
+            TokenType::MINUS_EQUAL | TokenType::MINUS_MINUS => TokenType::MINUS,
+            TokenType::STAR_EQUAL => TokenType::STAR,
+            TokenType::SLASH_EQUAL => TokenType::SLASH,
+            _ => TokenType::PERCENT,
+        };
+        Token::new(ttype, operator.as_string(), None, operator.line)
+    }
+
+    fn update(&mut self, target: Expr, operator: Token, prefix: bool) -> Result<Expr, LoxResult> {
+        if !Parser::is_assignable(&target) {
+            return Err(self.error(&operator, "Invalid increment target."));
+        }
+        Ok(Expr::Update(Rc::new(UpdateExpr {
+            target: Rc::new(target),
+            operator: Parser::arithmetic_operator(&operator),
+            prefix,
+        })))
+    }
+
+    // match (value) { pattern => expression, pattern => { statements } }
+    fn match_expression(&mut self) -> Result<Expr, LoxResult> {
+        let keyword = self.previous().dup();
+        self.consume(TokenType::LEFT_PAREN, "Expect '(' after 'match'.")?;
+        let subject = Rc::new(self.expression()?);
+        self.consume(TokenType::RIGHT_PAREN, "Expect ')' after match value.")?;
+        self.consume(TokenType::LEFT_BRACE, "Expect '{' before match arms.")?;
+
+        let mut arms = Vec::new();
+        while !self.check(TokenType::RIGHT_BRACE) && !self.is_at_end() {
+            let pattern = self.pattern()?;
+            self.consume(TokenType::ARROW, "Expect '=>' after pattern.")?;
+            let body = if self.is_match(&[TokenType::LEFT_BRACE]) {
+                let statements = Rc::new(self.block()?);
+                self.is_match(&[TokenType::COMMA]);
+                Rc::new(Stmt::Block(Rc::new(BlockStmt { statements })))
+            } else {
+                let expression = Rc::new(self.expression()?);
+                if !self.check(TokenType::RIGHT_BRACE) {
+                    self.consume(TokenType::COMMA, "Expect ',' after match arm.")?;
+                }
+                Rc::new(Stmt::Expression(Rc::new(ExpressionStmt { expression })))
+            };
+            arms.push(MatchArm { pattern, body });
+        }
+        self.consume(TokenType::RIGHT_BRACE, "Expect '}' after match arms.")?;
+
+        Ok(Expr::Match(Rc::new(MatchExpr {
+            keyword,
+            subject,
+            arms: Rc::new(arms),
+        })))
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, LoxResult> {
+        let first = self.single_pattern()?;
+        if !self.check(TokenType::PIPE) {
+            return Ok(first);
+        }
+        let mut alternatives = vec![first];
+        while self.is_match(&[TokenType::PIPE]) {
+            alternatives.push(self.single_pattern()?);
+        }
+        Ok(Pattern::Alternatives(alternatives))
+    }
+
+    fn single_pattern(&mut self) -> Result<Pattern, LoxResult> {
+        if self.is_match(&[TokenType::NUMBER, TokenType::STRING]) {
+            let token = self.previous().dup();
+            let value = token.literal.clone().unwrap();
+            return Ok(Pattern::Literal { token, value });
+        }
+        if self.is_match(&[TokenType::MINUS]) {
+            let token = self.consume(TokenType::NUMBER, "Expect number after '-' in pattern.")?;
+            let value = match &token.literal {
+                Some(LiteralValue::Int(n)) => LiteralValue::Int(-n),
+                Some(LiteralValue::BigInt(n)) => LiteralValue::integer(-n.as_ref()),
+                Some(LiteralValue::Num(n)) => LiteralValue::Num(-n),
+                _ => unreachable!(),
+            };
+            return Ok(Pattern::Literal { token, value });
+        }
+        if self.is_match(&[TokenType::TRUE, TokenType::FALSE, TokenType::NIL]) {
+            let token = self.previous().dup();
+            let value = match token.token_type() {
+                TokenType::TRUE => LiteralValue::Bool(true),
+                TokenType::FALSE => LiteralValue::Bool(false),
+                _ => LiteralValue::Nil,
+            };
+            return Ok(Pattern::Literal { token, value });
+        }
+        if self.is_match(&[TokenType::IDENTIFIER]) {
+            let name = self.previous().dup();
+            if name.as_string() == "_" {
+                return Ok(Pattern::Wildcard);
+            }
+            let mut class = Rc::new(Expr::Variable(Rc::new(VariableExpr { name })));
+            // A qualified name is an enum variant or a class in a module.
+            let qualified = self.is_match(&[TokenType::DOT]);
+            if qualified {
+                let member = self.consume(TokenType::IDENTIFIER, "Expect name after '.' in pattern.")?;
+                class = Rc::new(Expr::Get(Rc::new(GetExpr { literalvalue: class, name: member })));
+            }
+            if !self.is_match(&[TokenType::LEFT_PAREN]) {
+                return Ok(match class.deref() {
+                    Expr::Get(get) if qualified => Pattern::Constant { token: get.name.dup(), value: class.clone() },
+                    Expr::Variable(v) => Pattern::Binding(v.name.dup()),
+                    _ => unreachable!(),
+                });
+            }
+            let paren = self.previous().dup();
+            let mut fields = Vec::new();
+            if !self.check(TokenType::RIGHT_PAREN) {
+                fields.push(self.pattern()?);
+                while self.is_match(&[TokenType::COMMA]) {
+                    fields.push(self.pattern()?);
+                }
+            }
+            self.consume(TokenType::RIGHT_PAREN, "Expect ')' after class pattern fields.")?;
+            return Ok(Pattern::Instance { class, paren, fields });
+        }
+        let peek = self.peek().dup();
+        Err(self.error(&peek, "Expect pattern."))
+    }
+
+    // The else branch recurses so that `a ? b : c ? d : e` groups to the
+    // right, while the then branch may hold any expression up to the ':'.
+    fn conditional(&mut self) -> Result<Expr, LoxResult> {
+        let expr = self.coalesce()?;
+
+        if self.is_match(&[TokenType::QUESTION]) {
+            let then_branch = self.expression()?;
+            self.consume(TokenType::COLON, "Expect ':' after then branch of conditional expression.")?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional(Rc::new(ConditionalExpr {
+                condition: Rc::new(expr),
+                then_branch: Rc::new(then_branch),
+                else_branch: Rc::new(else_branch),
+            })));
+        }
+
+        Ok(expr)
+    }
+
+    fn coalesce(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr = self.or()?;
+
+        while self.is_match(&[TokenType::QUESTION_QUESTION]) {
+            let operator = self.previous().dup();
+            let right = Rc::new(self.or()?);
+            expr = Expr::Logical(Rc::new(LogicalExpr {
+                left: Rc::new(expr),
+                operator,
+                right,
+            }));
         }
 
         Ok(expr)
@@ -360,7 +1034,7 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
     fn comparison(&mut self) -> Result<Expr, LoxResult> {
-        let mut expr = self.term()?;
+        let mut expr = self.bit_or()?;
 
         while self.is_match(&[
             TokenType::GREATER,
@@ -368,6 +1042,70 @@ impl<'a> Parser<'a> {
             TokenType::LESS,
             TokenType::LESS_EQUAL,
         ]) {
+            let operator = self.previous().dup();
+            let right = self.bit_or()?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {
+                left: Rc::new(expr),
+                operator,
+                right: Rc::new(right),
+            }));
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_or(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr = self.bit_xor()?;
+
+        while self.is_match(&[TokenType::PIPE]) {
+            let operator = self.previous().dup();
+            let right = self.bit_xor()?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {
+                left: Rc::new(expr),
+                operator,
+                right: Rc::new(right),
+            }));
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_xor(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr = self.bit_and()?;
+
+        while self.is_match(&[TokenType::CARET]) {
+            let operator = self.previous().dup();
+            let right = self.bit_and()?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {
+                left: Rc::new(expr),
+                operator,
+                right: Rc::new(right),
+            }));
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_and(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr = self.shift()?;
+
+        while self.is_match(&[TokenType::AMPERSAND]) {
+            let operator = self.previous().dup();
+            let right = self.shift()?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {
+                left: Rc::new(expr),
+                operator,
+                right: Rc::new(right),
+            }));
+        }
+
+        Ok(expr)
+    }
+
+    fn shift(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr = self.term()?;
+
+        while self.is_match(&[TokenType::LESS_LESS, TokenType::GREATER_GREATER]) {
             let operator = self.previous().dup();
             let right = self.term()?;
             expr = Expr::Binary(Rc::new(BinaryExpr {
@@ -399,7 +1137,12 @@ impl<'a> Parser<'a> {
     fn factor(&mut self) -> Result<Expr, LoxResult> {
         let mut expr = self.unary()?;
 
-        while self.is_match(&[TokenType::SLASH, TokenType::STAR]) {
+        while self.is_match(&[
+            TokenType::SLASH,
+            TokenType::STAR,
+            TokenType::PERCENT,
+            TokenType::TILDE_SLASH,
+        ]) {
             let operator = self.previous().dup();
             let right = self.unary()?;
             expr = Expr::Binary(Rc::new(BinaryExpr {
@@ -413,7 +1156,13 @@ impl<'a> Parser<'a> {
     }
 
     fn unary(&mut self) -> Result<Expr, LoxResult> {
-        if self.is_match(&[TokenType::BANG, TokenType::MINUS]) {
+        if self.is_match(&[TokenType::PLUS_PLUS, TokenType::MINUS_MINUS]) {
+            let operator = self.previous().dup();
+            let target = self.unary()?;
+            return self.update(target, operator, true);
+        }
+
+        if self.is_match(&[TokenType::BANG, TokenType::MINUS, TokenType::TILDE]) {
             let operator = self.previous().dup();
             let right = self.unary()?;
             return Ok(Expr::Unary(Rc::new(UnaryExpr {
@@ -422,22 +1171,48 @@ impl<'a> Parser<'a> {
             })));
         }
 
-        self.call()
+        self.power()
+    }
+
+    // `**` binds tighter than a unary operator on its left but recurses into
+    // `unary` on its right, which makes it right-associative: 2 ** 3 ** 2 is
+    // 2 ** 9, and -2 ** 2 is -(2 ** 2).
+    fn power(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr = self.call()?;
+
+        if self.is_match(&[TokenType::PLUS_PLUS, TokenType::MINUS_MINUS]) {
+            let operator = self.previous().dup();
+            expr = self.update(expr, operator, false)?;
+        }
+
+        if self.is_match(&[TokenType::STAR_STAR]) {
+            let operator = self.previous().dup();
+            let right = self.unary()?;
+            return Ok(Expr::Binary(Rc::new(BinaryExpr {
+                left: Rc::new(expr),
+                operator,
+                right: Rc::new(right),
+            })));
+        }
+
+        Ok(expr)
     }
 
     fn finish_call(&mut self, callee: &Rc<Expr>) -> Result<Expr, LoxResult> {
         let mut arguments = Vec::new();
+        let mut names = Vec::new();
+        let mut named = Vec::new();
 
         if !self.check(TokenType::RIGHT_PAREN) {
-            arguments.push(Rc::new(self.expression()?));
+            self.argument(&mut arguments, &mut names, &mut named)?;
             while self.is_match(&[TokenType::COMMA]) {
-                if arguments.len() >= 255 {
+                if arguments.len() + named.len() >= 255 {
                     if !self.had_error {
                         let peek = self.peek().dup();
                         return Err(self.error(&peek, "Can't have more than 255 arguments."));
                     }
                 } else {
-                    arguments.push(Rc::new(self.expression()?));
+                    self.argument(&mut arguments, &mut names, &mut named)?;
                 }
             }
         }
@@ -448,27 +1223,74 @@ impl<'a> Parser<'a> {
             callee: Rc::clone(callee),
             paren,
             arguments,
+            names,
+            named,
         })))
     }
 
+    // A named argument is written `name: value` and, once one is used, the
+    // remaining arguments must be named too.
+    fn argument(
+        &mut self,
+        arguments: &mut Vec<Rc<Expr>>,
+        names: &mut Vec<Token>,
+        named: &mut Vec<Rc<Expr>>,
+    ) -> Result<(), LoxResult> {
+        if self.check(TokenType::IDENTIFIER) && self.check_next(TokenType::COLON) {
+            names.push(self.advance().dup());
+            self.advance();
+            named.push(Rc::new(self.expression()?));
+        } else if !names.is_empty() {
+            let peek = self.peek().dup();
+            return Err(self.error(&peek, "Positional argument can't follow a named argument."));
+        } else {
+            arguments.push(Rc::new(self.expression()?));
+        }
+        Ok(())
+    }
+
     fn call(&mut self) -> Result<Expr, LoxResult> {
         let mut expr = self.primary()?;
+        let mut optional = false;
 
         loop {
-            if self.is_match(&[TokenType::LEFT_PAREN]) {
+            if self.is_match(&[TokenType::QUESTION_DOT]) {
+                let name = self.consume(TokenType::IDENTIFIER, "Expect property name after '?.'.")?;
+                expr = Expr::OptionalGet(Rc::new(OptionalGetExpr { literalvalue: Rc::new(expr), name }));
+                optional = true;
+            } else if self.is_match(&[TokenType::LEFT_PAREN]) {
                 expr = self.finish_call(&Rc::new(expr))?;
             } else if self.is_match(&[TokenType::DOT]) {
                 let name = self.consume(TokenType:: IDENTIFIER, "Expect property name after  '.' .")?;
                 expr = Expr::Get(Rc::new(GetExpr { literalvalue: Rc::new(expr), name }))
+            } else if self.is_match(&[TokenType::LEFT_BRACKET]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RIGHT_BRACKET, "Expect ']' after index.")?;
+                expr = Expr::Index(Rc::new(IndexExpr {
+                    literalvalue: Rc::new(expr),
+                    bracket,
+                    index: Rc::new(index),
+                }));
             } else {
                 break;
             }
         }
 
+        // A nil receiver anywhere in the chain skips the rest of it, so the
+        // whole chain is wrapped to turn that short circuit back into nil.
+        if optional {
+            expr = Expr::Optional(Rc::new(OptionalExpr {
+                expression: Rc::new(expr),
+            }));
+        }
+
         Ok(expr)  
     }
 
     fn primary(&mut self) -> Result<Expr, LoxResult> {
+        if self.is_match(&[TokenType::MATCH]) {
+            return self.match_expression();
+        }
         if self.is_match(&[TokenType::FALSE]) {
             return Ok(Expr::Literal(Rc::new(LiteralExpr {
                 value: Some(LiteralValue::Bool(false)),
@@ -489,6 +1311,9 @@ impl<'a> Parser<'a> {
                 value: self.previous().literal.clone(),
             })));
         }
+        if self.is_match(&[TokenType::INTERPOLATION]) {
+            return self.interpolation();
+        }
         if self.is_match(&[TokenType::SUPER]) {
             let keyword = self.previous().dup();
             self.consume(TokenType::DOT, "Expect '.' after 'super'.")?;
@@ -508,6 +1333,46 @@ impl<'a> Parser<'a> {
             })));
         }
 
+        if self.is_match(&[TokenType::LEFT_BRACKET]) {
+            let bracket = self.previous().dup();
+            let mut elements = Vec::new();
+            if !self.check(TokenType::RIGHT_BRACKET) {
+                elements.push(Rc::new(self.expression()?));
+                while self.is_match(&[TokenType::COMMA]) {
+                    elements.push(Rc::new(self.expression()?));
+                }
+            }
+            self.consume(TokenType::RIGHT_BRACKET, "Expect ']' after list elements.")?;
+            return Ok(Expr::List(Rc::new(ListExpr { bracket, elements })));
+        }
+
+        if self.is_match(&[TokenType::LEFT_BRACE]) {
+            let brace = self.previous().dup();
+            let mut keys = Vec::new();
+            let mut values = Vec::new();
+            if !self.check(TokenType::RIGHT_BRACE) {
+                loop {
+                    keys.push(Rc::new(self.expression()?));
+                    self.consume(TokenType::COLON, "Expect ':' after map key.")?;
+                    values.push(Rc::new(self.expression()?));
+                    if !self.is_match(&[TokenType::COMMA]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenType::RIGHT_BRACE, "Expect '}' after map entries.")?;
+            return Ok(Expr::Map(Rc::new(MapExpr { brace, keys, values })));
+        }
+
+        if self.is_match(&[TokenType::FUN]) {
+            return self.lambda();
+        }
+
+        if self.check(TokenType::LEFT_PAREN) && self.is_arrow_function() {
+            self.advance();
+            return self.arrow_function();
+        }
+
         if self.is_match(&[TokenType::LEFT_PAREN]) {
             let expr = self.expression()?;
             self.consume(TokenType::RIGHT_PAREN, "Expect ')' after expression")?;
@@ -519,6 +1384,41 @@ impl<'a> Parser<'a> {
         Err(self.error(&peek, "Expect expression."))
     }
 
+    // `"a${x}b"` arrives as INTERPOLATION("a"), the tokens of `x`, then
+    // STRING("b"), and becomes `"a" + str(x) + "b"`.
+    fn interpolation(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr = Expr::Literal(Rc::new(LiteralExpr {
+            value: self.previous().literal.clone(),
+        }));
+
+        loop {
+            let plus = Token::new(TokenType::PLUS, "+".to_string(), None, self.previous().line);
+            let inner = self.expression()?;
+            expr = Expr::Binary(Rc::new(BinaryExpr {
+                left: Rc::new(expr),
+                operator: plus.dup(),
+                right: Rc::new(Expr::Stringify(Rc::new(StringifyExpr {
+                    expression: Rc::new(inner),
+                }))),
+            }));
+
+            let more = self.is_match(&[TokenType::INTERPOLATION]);
+            if !more {
+                self.consume(TokenType::STRING, "Expect '}' after interpolated expression.")?;
+            }
+            expr = Expr::Binary(Rc::new(BinaryExpr {
+                left: Rc::new(expr),
+                operator: plus,
+                right: Rc::new(Expr::Literal(Rc::new(LiteralExpr {
+                    value: self.previous().literal.clone(),
+                }))),
+            }));
+            if !more {
+                return Ok(expr);
+            }
+        }
+    }
+
     fn consume(&mut self, ttype: TokenType, message: &str) -> Result<Token, LoxResult> {
         if self.check(ttype) {
             Ok(self.advance().dup())
@@ -544,13 +1444,21 @@ impl<'a> Parser<'a> {
             if matches!(
                 self.peek().token_type(),
                 TokenType::CLASS
+                    | TokenType::ENUM
+                    | TokenType::TRAIT
                     | TokenType::FUN
                     | TokenType::VAR
+                    | TokenType::CONST
                     | TokenType::FOR
                     | TokenType::IF
                     | TokenType::WHILE
                     | TokenType::PRINT
                     | TokenType::RETURN
+                    | TokenType::THROW
+                    | TokenType::TRY
+                    | TokenType::IMPORT
+                    | TokenType::FROM
+                    | TokenType::EXPORT
             ) {
                 return;
             }
@@ -578,6 +1486,14 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn check_next(&self, ttype: TokenType) -> bool {
+        self.token_is(self.current + 1, ttype)
+    }
+
+    fn token_is(&self, index: usize, ttype: TokenType) -> bool {
+        matches!(self.tokens.get(index), Some(t) if t.is(ttype))
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -0,0 +1,48 @@
+use std::rc::Rc;
+
+use crate::entities::*;
+use crate::expr::*;
+use crate::stmt::*;
+
+// Patterns only appear in match arms, so they are plain data rather than
+// generated AST nodes. The class in an instance pattern, and the value of a
+// qualified name such as `Color.Red`, are expressions so that the resolver
+// can resolve them like any other.
+pub enum Pattern {
+    Literal { token: Token, value: LiteralValue },
+    Constant { token: Token, value: Rc<Expr> },
+    Wildcard,
+    Binding(Token),
+    Alternatives(Vec<Pattern>),
+    Instance { class: Rc<Expr>, paren: Token, fields: Vec<Pattern> },
+}
+
+impl Pattern {
+    // A pattern that matches every value, which makes any arms after it
+    // unreachable.
+    pub fn is_catch_all(&self) -> bool {
+        match self {
+            Pattern::Wildcard | Pattern::Binding(_) => true,
+            Pattern::Alternatives(alternatives) => alternatives.iter().any(Pattern::is_catch_all),
+            _ => false,
+        }
+    }
+
+    pub fn bindings(&self) -> Vec<&Token> {
+        match self {
+            Pattern::Binding(name) => vec![name],
+            Pattern::Alternatives(alternatives) => {
+                alternatives.iter().flat_map(Pattern::bindings).collect()
+            }
+            Pattern::Instance { fields, .. } => fields.iter().flat_map(Pattern::bindings).collect(),
+            Pattern::Literal { .. } | Pattern::Constant { .. } | Pattern::Wildcard => Vec::new(),
+        }
+    }
+}
+
+// An arm's body is an expression statement, whose value becomes the value
+// of the match, or a block, which makes the match evaluate to nil.
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Rc<Stmt>,
+}
@@ -7,15 +7,25 @@ use crate::errors::*;
 use crate::expr::*;
 use crate::interpreter::*;
 use crate::entities::*;
+use crate::pattern::*;
 use crate::stmt::*;
 
 pub struct Resolver<'a> {
     interpreter: &'a Interpreter,
-    scopes: RefCell<Vec<RefCell<HashMap<String, bool>>>>,
+    scopes: RefCell<Vec<RefCell<HashMap<String, Binding>>>>,
     had_error: RefCell<bool>,
     current_function: RefCell<FunctionType>,
     current_class: RefCell<ClassType>,
     in_while: RefCell<bool>,
+    in_match: RefCell<bool>,
+}
+
+// What a scope knows about a name: whether its initializer has finished and
+// whether it may be assigned to.
+#[derive(PartialEq)]
+struct Binding {
+    defined: bool,
+    constant: bool,
 }
 
 #[derive(PartialEq)]
@@ -30,6 +40,7 @@ enum ClassType {
     None,
     Class,
     Subclass,
+    Trait,
 }
 
 impl<'a> StmtVisitor<()> for Resolver<'a> {
@@ -45,6 +56,20 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
         }
         Ok(())
     }
+    // A match is an expression, and a generator can only be suspended
+    // between statements, so a yield can't appear inside one.
+    fn visit_yield_stmt(&self, _: Rc<Stmt>, stmt: &YieldStmt) -> Result<(), LoxResult> {
+        match *self.current_function.borrow() {
+            FunctionType::None => self.error(&stmt.keyword, "Can't yield from top-level code."),
+            FunctionType::Initializer => self.error(&stmt.keyword, "Can't yield from an initializer."),
+            _ if *self.in_match.borrow() => self.error(&stmt.keyword, "Can't yield inside a match."),
+            _ => {}
+        }
+        if let Some(value) = stmt.value.clone() {
+            self.resolve_expr(value)?;
+        }
+        Ok(())
+    }
     fn visit_function_stmt(&self, _: Rc<Stmt>, stmt: &FunctionStmt) -> Result<(), LoxResult> {
         self.declare(&stmt.name);
         self.define(&stmt.name);
@@ -61,14 +86,75 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
         
         Ok(())
     }
+    fn visit_continue_stmt(&self, _: Rc<Stmt>, stmt: &ContinueStmt) -> Result<(), LoxResult> {
+        if !*self.in_while.borrow() {
+            self.error(&stmt.token, "Continue statement outside of a while/for loop.");
+        }
+        Ok(())
+    }
     fn visit_while_stmt(&self, _: Rc<Stmt>, stmt: &WhileStmt) -> Result<(), LoxResult> {
         let previous_nesting = self.in_while.replace(true);
         self.resolve_expr(stmt.condition.clone())?;
         self.resolve_stmt(stmt.body.clone())?;
+        if let Some(increment) = stmt.increment.clone() {
+            self.resolve_expr(increment)?;
+        }
 
         self.in_while.replace(previous_nesting);
         Ok(())
     }
+    fn visit_forin_stmt(&self, _: Rc<Stmt>, stmt: &ForInStmt) -> Result<(), LoxResult> {
+        self.resolve_expr(stmt.iterable.clone())?;
+        let previous_nesting = self.in_while.replace(true);
+        self.begin_scope();
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+        self.resolve_stmt(stmt.body.clone())?;
+        self.end_scope();
+        self.in_while.replace(previous_nesting);
+        Ok(())
+    }
+    fn visit_throw_stmt(&self, _: Rc<Stmt>, stmt: &ThrowStmt) -> Result<(), LoxResult> {
+        self.resolve_expr(stmt.value.clone())
+    }
+    // Import paths are relative to the file being loaded, which is only
+    // known while its top-level code runs, so functions can't import.
+    fn visit_import_stmt(&self, _: Rc<Stmt>, stmt: &ImportStmt) -> Result<(), LoxResult> {
+        if *self.current_function.borrow() != FunctionType::None {
+            self.error(&stmt.keyword, "Can't import inside a function.");
+        }
+        for name in stmt.alias.iter().chain(stmt.names.iter()) {
+            self.declare(name);
+            self.define(name);
+        }
+        Ok(())
+    }
+    fn visit_export_stmt(&self, _: Rc<Stmt>, stmt: &ExportStmt) -> Result<(), LoxResult> {
+        if !self.scopes.borrow().is_empty() {
+            self.error(&stmt.keyword, "Can only export from the top level of a module.");
+        }
+        self.resolve_stmt(stmt.declaration.clone())
+    }
+    fn visit_try_stmt(&self, _: Rc<Stmt>, stmt: &TryStmt) -> Result<(), LoxResult> {
+        self.begin_scope();
+        self.resolve(&stmt.body)?;
+        self.end_scope();
+
+        if let (Some(name), Some(catch_body)) = (&stmt.catch_name, &stmt.catch_body) {
+            self.begin_scope();
+            self.declare(name);
+            self.define(name);
+            self.resolve(catch_body)?;
+            self.end_scope();
+        }
+
+        if let Some(finally_body) = &stmt.finally_body {
+            self.begin_scope();
+            self.resolve(finally_body)?;
+            self.end_scope();
+        }
+        Ok(())
+    }
     fn visit_if_stmt(&self, _: Rc<Stmt>, stmt: &IfStmt) -> Result<(), LoxResult> {
         self.resolve_expr(stmt.condition.clone())?;
         self.resolve_stmt(stmt.then_branch.clone())?;
@@ -94,6 +180,35 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
         Ok(())
     }
 
+    fn visit_destructure_stmt(&self, _: Rc<Stmt>, stmt: &DestructureStmt) -> Result<(), LoxResult> {
+        for name in stmt.names.iter() {
+            self.declare(name);
+        }
+        self.resolve_expr(stmt.initializer.clone())?;
+        for name in stmt.names.iter() {
+            self.define(name);
+        }
+        Ok(())
+    }
+
+    fn visit_multiassign_stmt(&self, _: Rc<Stmt>, stmt: &MultiAssignStmt) -> Result<(), LoxResult> {
+        for value in stmt.values.iter() {
+            self.resolve_expr(value.clone())?;
+        }
+        for target in stmt.targets.iter() {
+            self.check_assignable(target);
+            self.resolve_expr(target.clone())?;
+        }
+        Ok(())
+    }
+
+    fn visit_const_stmt(&self, _: Rc<Stmt>, stmt: &ConstStmt) -> Result<(), LoxResult> {
+        self.declare(&stmt.name);
+        self.resolve_expr(stmt.initializer.clone())?;
+        self.define_constant(&stmt.name);
+        Ok(())
+    }
+
     fn visit_var_stmt(&self, _: Rc<Stmt>, stmt: &VarStmt) -> Result<(), LoxResult> {
         self.declare(&stmt.name);
         if let Some(init) = stmt.initializer.clone() {
@@ -103,28 +218,79 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
         Ok(())
     }
     
+    // Trait methods are resolved like a subclass's, since wherever the trait
+    // is used there is a `super` beneath it.
+    fn visit_trait_stmt(&self, _: Rc<Stmt>, stmt: &TraitStmt) -> Result<(), LoxResult> {
+        let enclosing_class = self.current_class.replace(ClassType::Trait);
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+
+        self.begin_scope();
+        self.scopes
+            .borrow()
+            .last()
+            .unwrap()
+            .borrow_mut()
+            .insert("super".to_string(), Binding { defined: true, constant: false });
+        self.begin_scope();
+        self.scopes
+            .borrow()
+            .last()
+            .unwrap()
+            .borrow_mut()
+            .insert("this".to_string(), Binding { defined: true, constant: false });
+        for method in stmt.methods.deref() {
+            if let Stmt::Function(method) = method.deref() {
+                let declaration = if method.name.as_string() == "init" {
+                    FunctionType::Initializer
+                } else {
+                    FunctionType::Method
+                };
+                self.resolve_function(method, declaration)?;
+            }
+        }
+        self.end_scope();
+        self.end_scope();
+
+        self.current_class.replace(enclosing_class);
+        Ok(())
+    }
+
+    fn visit_enum_stmt(&self, _: Rc<Stmt>, stmt: &EnumStmt) -> Result<(), LoxResult> {
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+        Ok(())
+    }
+
     fn visit_class_stmt(&self, _: Rc<Stmt>, stmt: &ClassStmt) -> Result<(), LoxResult> {
         let enclosing_class = self.current_class.replace(ClassType::Class);
         self.declare(&stmt.name);
         self.define(&stmt.name);
 
         if let Some(superclass) = &stmt.superclass {
-            self.current_class.replace(ClassType::Subclass);
-
             if let Expr::Variable(v) = &superclass.deref() {
                 if v.name.as_string() == stmt.name.as_string() {
                     self.error(&v.name, "A class can't inherit from itself");
                 }
             }
             self.resolve_expr(superclass.clone())?;
+        }
+        for used in stmt.traits.iter() {
+            self.resolve_expr(used.clone())?;
+        }
 
+        // Traits sit between a class and its superclass, so a class that uses
+        // one has a `super` even without a superclass.
+        let has_super = stmt.superclass.is_some() || !stmt.traits.is_empty();
+        if has_super {
+            self.current_class.replace(ClassType::Subclass);
             self.begin_scope();
             self.scopes
                 .borrow()
                 .last()
                 .unwrap()
                 .borrow_mut()
-                .insert("super".to_string(), true);
+                .insert("super".to_string(), Binding { defined: true, constant: false });
         }
 
         self.begin_scope();
@@ -133,7 +299,7 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
             .last()
             .unwrap()
             .borrow_mut()
-            .insert("this".to_string(), true);
+            .insert("this".to_string(), Binding { defined: true, constant: false });
         for method in stmt.methods.deref() {
 
             if let Stmt::Function(method) = method.deref() {
@@ -150,13 +316,43 @@ impl<'a> StmtVisitor<()> for Resolver<'a> {
                 ));
             }
         }
+        for accessor in stmt.getters.iter().chain(stmt.setters.iter()) {
+            if let Stmt::Function(accessor) = accessor.deref() {
+                self.resolve_function(accessor, FunctionType::Method)?;
+            } else {
+                return Err(LoxResult::runtime_error(
+                    &stmt.name,
+                    "Class method did not resolve into a function statement",
+                ));
+            }
+        }
+        for method in stmt.static_methods.deref() {
+            if let Stmt::Function(method) = method.deref() {
+                self.resolve_function(method, FunctionType::Method)?;
+            } else {
+                return Err(LoxResult::runtime_error(
+                    &stmt.name,
+                    "Class method did not resolve into a function statement",
+                ));
+            }
+        }
         self.end_scope();
-        if stmt.superclass.is_some() {
+        if has_super {
             self.end_scope();
         }
  
         self.current_class.replace(enclosing_class);
 
+        // Static field initializers are evaluated in the scope enclosing the
+        // class, after the class itself has been defined.
+        for field in stmt.static_fields.deref() {
+            if let Stmt::Var(var) = field.deref() {
+                if let Some(initializer) = &var.initializer {
+                    self.resolve_expr(initializer.clone())?;
+                }
+            }
+        }
+
         Ok(())
     }
 }
@@ -172,18 +368,58 @@ impl<'a> ExprVisitor<()> for Resolver<'a> {
     }
     fn visit_call_expr(&self, _: Rc<Expr>, expr: &CallExpr) -> Result<(), LoxResult> {
         self.resolve_expr(expr.callee.clone())?;
-        for argument in expr.arguments.iter() {
+        for argument in expr.arguments.iter().chain(expr.named.iter()) {
             self.resolve_expr(argument.clone())?;
         }
 
         Ok(())
     }
+    fn visit_compound_expr(&self, _: Rc<Expr>, expr: &CompoundExpr) -> Result<(), LoxResult> {
+        self.check_assignable(&expr.target);
+        self.resolve_expr(expr.target.clone())?;
+        self.resolve_expr(expr.value.clone())?;
+        Ok(())
+    }
+    fn visit_update_expr(&self, _: Rc<Expr>, expr: &UpdateExpr) -> Result<(), LoxResult> {
+        self.check_assignable(&expr.target);
+        self.resolve_expr(expr.target.clone())?;
+        Ok(())
+    }
+    fn visit_match_expr(&self, _: Rc<Expr>, expr: &MatchExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.subject.clone())?;
+        let enclosing_match = self.in_match.replace(true);
+        for arm in expr.arms.iter() {
+            self.resolve_pattern_classes(&arm.pattern)?;
+            self.begin_scope();
+            self.resolve_pattern(&arm.pattern)?;
+            self.resolve_stmt(arm.body.clone())?;
+            self.end_scope();
+        }
+        self.in_match.replace(enclosing_match);
+        if !expr.arms.iter().any(|arm| arm.pattern.is_catch_all()) {
+            self.warning(&expr.keyword, "Match has no catch-all arm ('_' or a binding).");
+        }
+        Ok(())
+    }
+    fn visit_conditional_expr(&self, _: Rc<Expr>, expr: &ConditionalExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.condition.clone())?;
+        self.resolve_expr(expr.then_branch.clone())?;
+        self.resolve_expr(expr.else_branch.clone())?;
+        Ok(())
+    }
+    fn visit_optional_expr(&self, _: Rc<Expr>, expr: &OptionalExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.expression.clone())
+    }
+    fn visit_optionalget_expr(&self, _: Rc<Expr>, expr: &OptionalGetExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.literalvalue.clone())
+    }
     fn visit_logical_expr(&self, _: Rc<Expr>, expr: &LogicalExpr) -> Result<(), LoxResult> {
         self.resolve_expr(expr.left.clone())?;
         self.resolve_expr(expr.right.clone())?;
         Ok(())
     }
     fn visit_assign_expr(&self, wrapper: Rc<Expr>, expr: &AssignExpr) -> Result<(), LoxResult> {
+        self.check_assignable(&wrapper);
         self.resolve_expr(expr.value.clone())?;
         self.resolve_local(wrapper, &expr.name);
         Ok(())
@@ -216,7 +452,7 @@ impl<'a> ExprVisitor<()> for Resolver<'a> {
                 .unwrap()
                 .borrow()
                 .get(&expr.name.as_string())
-                == Some(&false)
+                .is_some_and(|binding| !binding.defined)
         {
             self.error(
                 &expr.name,
@@ -240,12 +476,50 @@ impl<'a> ExprVisitor<()> for Resolver<'a> {
         Ok(())
     }
     
-    fn visit_super_expr(&self, _: Rc<Expr>, expr: &SuperExpr) -> Result<(), LoxResult> {
+    fn visit_lambda_expr(&self, _: Rc<Expr>, expr: &LambdaExpr) -> Result<(), LoxResult> {
+        self.resolve_function(&expr.declaration, FunctionType::Function)
+    }
+
+    fn visit_list_expr(&self, _: Rc<Expr>, expr: &ListExpr) -> Result<(), LoxResult> {
+        for element in expr.elements.iter() {
+            self.resolve_expr(element.clone())?;
+        }
+        Ok(())
+    }
+
+    fn visit_map_expr(&self, _: Rc<Expr>, expr: &MapExpr) -> Result<(), LoxResult> {
+        for (key, value) in expr.keys.iter().zip(expr.values.iter()) {
+            self.resolve_expr(key.clone())?;
+            self.resolve_expr(value.clone())?;
+        }
+        Ok(())
+    }
+
+    fn visit_index_expr(&self, _: Rc<Expr>, expr: &IndexExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.literalvalue.clone())?;
+        self.resolve_expr(expr.index.clone())?;
+        Ok(())
+    }
+
+    fn visit_indexset_expr(&self, _: Rc<Expr>, expr: &IndexSetExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.value.clone())?;
+        self.resolve_expr(expr.literalvalue.clone())?;
+        self.resolve_expr(expr.index.clone())?;
+        Ok(())
+    }
+
+    fn visit_stringify_expr(&self, _: Rc<Expr>, expr: &StringifyExpr) -> Result<(), LoxResult> {
+        self.resolve_expr(expr.expression.clone())
+    }
+
+    fn visit_super_expr(&self, wrapper: Rc<Expr>, expr: &SuperExpr) -> Result<(), LoxResult> {
         match self.current_class.borrow().deref() {
             ClassType::None => {
                 self.error(&expr.keyword, "Can't use 'super' outside of a class.");
             }
-            ClassType::Subclass => {}
+            ClassType::Subclass | ClassType::Trait => {
+                self.resolve_local(wrapper, &expr.keyword);
+            }
             _ => {
                 self.error(&expr.keyword, "Can't use 'super' in a class with no superclass");
             }
@@ -264,6 +538,7 @@ impl<'a> Resolver<'a> {
             current_function: RefCell::new(FunctionType::None),
             current_class: RefCell::new(ClassType::None),
             in_while: RefCell::new(false),
+            in_match: RefCell::new(false),
         }
     }
 
@@ -299,13 +574,37 @@ impl<'a> Resolver<'a> {
             if scope.borrow().contains_key(&name.as_string()) {
                 self.error(name, "Already a variable with this name in this scope.");
             }
-            scope.borrow_mut().insert(name.as_string(), false);
+            scope.borrow_mut().insert(name.as_string(), Binding { defined: false, constant: false });
         }
     }
 
     fn define(&self, name: &Token) {
         if let Some(scope) = self.scopes.borrow().last() {
-            scope.borrow_mut().insert(name.as_string(), true);
+            scope.borrow_mut().insert(name.as_string(), Binding { defined: true, constant: false });
+        }
+    }
+
+    fn define_constant(&self, name: &Token) {
+        if let Some(scope) = self.scopes.borrow().last() {
+            scope.borrow_mut().insert(name.as_string(), Binding { defined: true, constant: true });
+        }
+    }
+
+    // Only locals are checked here. Globals are unknown to the resolver, so
+    // assigning to a global constant is caught by the environment at runtime.
+    fn check_assignable(&self, target: &Expr) {
+        let name = match target {
+            Expr::Variable(v) => &v.name,
+            Expr::Assign(a) => &a.name,
+            _ => return,
+        };
+        for scope in self.scopes.borrow().iter().rev() {
+            if let Some(binding) = scope.borrow().get(&name.as_string()) {
+                if binding.constant {
+                    self.error(name, &format!("Cannot assign to constant '{}'.", name.as_string()));
+                }
+                return;
+            }
         }
     }
 
@@ -319,10 +618,18 @@ impl<'a> Resolver<'a> {
     }
 
     fn resolve_function(&self, function: &FunctionStmt, ftype: FunctionType) -> Result<(), LoxResult> {
+        // Defaults are evaluated in the function's closure, so they are
+        // resolved in the enclosing scope and can't see other parameters.
+        for default in function.defaults.iter().flatten() {
+            self.resolve_expr(default.clone())?;
+        }
+
         let enclosing_function = self.current_function.replace(ftype);
+        let enclosing_loop = self.in_while.replace(false);
+        let enclosing_match = self.in_match.replace(false);
         self.begin_scope();
 
-        for param in function.params.iter() {
+        for param in function.params.iter().chain(function.rest.iter()) {
             self.declare(param);
             self.define(param);
         }
@@ -330,6 +637,8 @@ impl<'a> Resolver<'a> {
         self.resolve(&function.body)?;
 
         self.end_scope();
+        self.in_match.replace(enclosing_match);
+        self.in_while.replace(enclosing_loop);
         self.current_function.replace(enclosing_function);
         Ok(())
     }
@@ -338,4 +647,56 @@ impl<'a> Resolver<'a> {
         self.had_error.replace(true);
         LoxResult::parse_error(token, message);
     }
+
+    // Warnings are reported like errors but don't stop the program running.
+    fn warning(&self, token: &Token, message: &str) {
+        eprintln!("[line {}] Warning at '{}': {}", token.line, token.as_string(), message);
+    }
+
+    // The interpreter evaluates a pattern's classes and constants before the
+    // arm's scope exists, so they are resolved outside it.
+    fn resolve_pattern_classes(&self, pattern: &Pattern) -> Result<(), LoxResult> {
+        match pattern {
+            Pattern::Alternatives(alternatives) => {
+                for alternative in alternatives {
+                    self.resolve_pattern_classes(alternative)?;
+                }
+            }
+            Pattern::Instance { class, fields, .. } => {
+                self.resolve_expr(class.clone())?;
+                for field in fields {
+                    self.resolve_pattern_classes(field)?;
+                }
+            }
+            Pattern::Constant { value, .. } => self.resolve_expr(value.clone())?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // An alternative can't bind names, since which of its branches matched
+    // decides which names would be defined.
+    fn resolve_pattern(&self, pattern: &Pattern) -> Result<(), LoxResult> {
+        match pattern {
+            Pattern::Binding(name) => {
+                self.declare(name);
+                self.define(name);
+            }
+            Pattern::Alternatives(alternatives) => {
+                for alternative in alternatives {
+                    if let Some(name) = alternative.bindings().first() {
+                        self.error(name, "Can't bind names in an alternative pattern.");
+                    }
+                    self.resolve_pattern(alternative)?;
+                }
+            }
+            Pattern::Instance { fields, .. } => {
+                for field in fields {
+                    self.resolve_pattern(field)?;
+                }
+            }
+            Pattern::Literal { .. } | Pattern::Constant { .. } | Pattern::Wildcard => {}
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -4,6 +4,7 @@
 // TODO: (possibly) add /* */ multiline comment support (with nesting)
 use crate::entities::*;
 use crate::errors::*;
+use crate::lox_bigint::*;
 //use std::collections::HashMap;
 pub struct Scanner {
     source: Vec<char>,
@@ -11,6 +12,8 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: usize,
+    // Brace depth inside each `${` that is still open, innermost last.
+    interpolations: Vec<usize>,
 }
 impl Scanner {
     pub fn new(src: String) -> Scanner {
@@ -20,6 +23,7 @@ impl Scanner {
             start: 0,
             current: 0,
             line: 1,
+            interpolations: Vec::new(),
         }
     }
     pub fn scan(&mut self) -> Result<&Vec<Token>, LoxResult> {
@@ -33,6 +37,9 @@ impl Scanner {
                 }
             }
         }
+        if !self.interpolations.is_empty() {
+            had_error = Some(LoxResult::error(self.line, "Unterminated string interpolation."));
+        }
         self.tokens.push(Token::eof(self.line));
 
         if let Some(e) = had_error {
@@ -49,14 +56,98 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LEFT_PAREN),
             ')' => self.add_token(TokenType::RIGHT_PAREN),
-            '{' => self.add_token(TokenType::LEFT_BRACE),
-            '}' => self.add_token(TokenType::RIGHT_BRACE),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LEFT_BRACE);
+            }
+            '}' => match self.interpolations.last_mut() {
+                // This brace closes a `${`, so the string picks up again.
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string()?;
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RIGHT_BRACE);
+                }
+                None => self.add_token(TokenType::RIGHT_BRACE),
+            },
+            '[' => self.add_token(TokenType::LEFT_BRACKET),
+            ']' => self.add_token(TokenType::RIGHT_BRACKET),
+            ':' => self.add_token(TokenType::COLON),
+            '?' => {
+                let tk = if self.equal_differentiator('?') {
+                    TokenType::QUESTION_QUESTION
+                } else if self.equal_differentiator('.') {
+                    TokenType::QUESTION_DOT
+                } else {
+                    TokenType::QUESTION
+                };
+                self.add_token(tk);
+            }
             ',' => self.add_token(TokenType::COMMA),
-            '.' => self.add_token(TokenType::DOT),
-            '-' => self.add_token(TokenType::MINUS),
-            '+' => self.add_token(TokenType::PLUS),
+            '.' => {
+                if self.peak() == Some('.') && self.peak_next() == Some('.') {
+                    self.advance();
+                    self.advance();
+                    self.add_token(TokenType::DOT_DOT_DOT);
+                } else {
+                    self.add_token(TokenType::DOT);
+                }
+            }
+            '-' => {
+                let tk = if self.equal_differentiator('-') {
+                    TokenType::MINUS_MINUS
+                } else if self.equal_differentiator('=') {
+                    TokenType::MINUS_EQUAL
+                } else {
+                    TokenType::MINUS
+                };
+                self.add_token(tk);
+            }
+            '+' => {
+                let tk = if self.equal_differentiator('+') {
+                    TokenType::PLUS_PLUS
+                } else if self.equal_differentiator('=') {
+                    TokenType::PLUS_EQUAL
+                } else {
+                    TokenType::PLUS
+                };
+                self.add_token(tk);
+            }
             ';' => self.add_token(TokenType::SEMICOLON),
-            '*' => self.add_token(TokenType::STAR),
+            '*' => {
+                let tk = if self.equal_differentiator('*') {
+                    TokenType::STAR_STAR
+                } else if self.equal_differentiator('=') {
+                    TokenType::STAR_EQUAL
+                } else {
+                    TokenType::STAR
+                };
+                self.add_token(tk);
+            }
+            '%' => {
+                let tk = if self.equal_differentiator('=') {
+                    TokenType::PERCENT_EQUAL
+                } else {
+                    TokenType::PERCENT
+                };
+                self.add_token(tk);
+            }
+            '&' => self.add_token(TokenType::AMPERSAND),
+            '|' => self.add_token(TokenType::PIPE),
+            '^' => self.add_token(TokenType::CARET),
+            // `//` already starts a comment, so integer division is `~/`.
+            '~' => {
+                let tk = if self.equal_differentiator('/') {
+                    TokenType::TILDE_SLASH
+                } else {
+                    TokenType::TILDE
+                };
+                self.add_token(tk);
+            }
             '!' => {
                 let tk = if self.equal_differentiator('=') {
                     TokenType::BANG_EQUAL
@@ -69,6 +160,8 @@ impl Scanner {
             '=' => {
                 let tk = if self.equal_differentiator('=') {
                     TokenType::EQUAL_EQUAL
+                } else if self.equal_differentiator('>') {
+                    TokenType::ARROW
                 } else {
                     TokenType::EQUAL
                 };
@@ -77,6 +170,8 @@ impl Scanner {
             '<' => {
                 let tk = if self.equal_differentiator('=') {
                     TokenType::LESS_EQUAL
+                } else if self.equal_differentiator('<') {
+                    TokenType::LESS_LESS
                 } else {
                     TokenType::LESS
                 };
@@ -85,6 +180,8 @@ impl Scanner {
             '>' => {
                 let tk = if self.equal_differentiator('=') {
                     TokenType::GREATER_EQUAL
+                } else if self.equal_differentiator('>') {
+                    TokenType::GREATER_GREATER
                 } else {
                     TokenType::GREATER
                 };
@@ -102,6 +199,8 @@ impl Scanner {
                 } else if self.equal_differentiator('*') {
                     // block comment start
                     self.scan_comment()?;
+                } else if self.equal_differentiator('=') {
+                    self.add_token(TokenType::SLASH_EQUAL);
                 } else {
                     self.add_token(TokenType::SLASH);
                 }
@@ -168,24 +267,28 @@ impl Scanner {
         self.add_token(TokenType::IDENTIFIER);
         }
     }
+    // A literal with a decimal point is a float and one without is an
+    // integer, big if it doesn't fit in an i64.
     fn number(&mut self) {
         while Scanner::is_digit(self.peak()) {
             self.advance();
         }
-        if let Some(ch) = self.peak() {
-            if ch == '.' {
-                if Scanner::is_digit(self.peak_next()) {
-                    self.advance();
+        let mut is_float = false;
+        if self.peak() == Some('.') && Scanner::is_digit(self.peak_next()) {
+            is_float = true;
+            self.advance();
 
-                    while Scanner::is_digit(self.peak()) {
-                        self.advance();
-                    }
-                }
+            while Scanner::is_digit(self.peak()) {
+                self.advance();
             }
         }
         let value: String = self.source[self.start..self.current].iter().collect();
-        let num: f64 = value.parse().unwrap();
-        self.add_token_object(TokenType::NUMBER, Some(LiteralValue::Num(num)));
+        let literal = if is_float {
+            LiteralValue::Num(value.parse().unwrap())
+        } else {
+            LiteralValue::integer(BigInt::parse(&value).unwrap())
+        };
+        self.add_token_object(TokenType::NUMBER, Some(literal));
     }
     fn peak_next(&self) -> Option<char> {
         self.source.get(self.current + 1).copied()
@@ -204,29 +307,99 @@ impl Scanner {
             false
         }
     }
+    // Scans string contents up to the closing quote, or up to a `${` in which
+    // case the text so far becomes an INTERPOLATION token and the scanner goes
+    // back to ordinary tokens until the matching `}`.
     fn string(&mut self) -> Result<(), LoxResult> {
-        while let Some(ch) = self.peak() {
-            match ch {
-                '"' => {
+        let mut value = String::new();
+        let mut error = None;
+        loop {
+            match self.peak() {
+                None => {
+                    return Err(LoxResult::error(self.line, "Unterminated String."));
+                }
+                Some('"') => {
+                    self.advance();
                     break;
                 }
-                '\n' => {
-                    self.line += 1;
+                Some('\\') => {
+                    self.advance();
+                    match self.escape() {
+                        Ok(ch) => value.push(ch),
+                        Err(e) => error = Some(e),
+                    }
+                }
+                Some('$') if self.peak_next() == Some('{') => {
+                    self.advance();
+                    self.advance();
+                    if self.peak() == Some('}') {
+                        error = Some(LoxResult::error(self.line, "Expect expression inside '${}'."));
+                    }
+                    self.add_token_object(TokenType::INTERPOLATION, Some(LiteralValue::Str(value)));
+                    self.interpolations.push(0);
+                    return error.map_or(Ok(()), Err);
+                }
+                Some(ch) => {
+                    if ch == '\n' {
+                        self.line += 1;
+                    }
+                    value.push(ch);
+                    self.advance();
                 }
-                _ => {}
             }
-            self.advance();
-        }
-        if self.is_at_end() {
-            return Err(LoxResult::error(self.line, "Unterminated String."));
         }
-        self.advance();
 
-        let value: String = self.source[self.start + 1..self.current - 1]
-            .iter()
-            .collect();
         self.add_token_object(TokenType::STRING, Some(LiteralValue::Str(value)));
-        Ok(())
+        error.map_or(Ok(()), Err)
+    }
+
+    fn escape(&mut self) -> Result<char, LoxResult> {
+        let ch = match self.peak() {
+            Some(ch) => ch,
+            None => return Err(LoxResult::error(self.line, "Unterminated String.")),
+        };
+        self.advance();
+        match ch {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(),
+            _ => Err(LoxResult::error(
+                self.line,
+                &format!("Invalid escape sequence '\\{ch}'."),
+            )),
+        }
+    }
+
+    // Handles the `{1F600}` part of a `\u{1F600}` escape.
+    fn unicode_escape(&mut self) -> Result<char, LoxResult> {
+        if !self.equal_differentiator('{') {
+            return Err(LoxResult::error(self.line, "Expect '{' after '\\u'."));
+        }
+        let mut digits = String::new();
+        while let Some(ch) = self.peak() {
+            if ch == '}' || ch == '"' || digits.len() > 6 {
+                break;
+            }
+            digits.push(ch);
+            self.advance();
+        }
+        if !self.equal_differentiator('}') {
+            return Err(LoxResult::error(self.line, "Unterminated unicode escape."));
+        }
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .filter(|_| !digits.is_empty() && digits.len() <= 6)
+            .and_then(char::from_u32)
+            .ok_or_else(|| {
+                LoxResult::error(
+                    self.line,
+                    &format!("Invalid unicode escape '\\u{{{digits}}}'."),
+                )
+            })
     }
     fn advance(&mut self) -> char {
         let result = *self.source.get(self.current).unwrap();
@@ -276,7 +449,24 @@ impl Scanner {
             "true" => Some(TokenType::TRUE),
             "var" => Some(TokenType::VAR),
             "while" => Some(TokenType::WHILE),
+            "throw" => Some(TokenType::THROW),
+            "try" => Some(TokenType::TRY),
+            "catch" => Some(TokenType::CATCH),
+            "finally" => Some(TokenType::FINALLY),
+            "static" => Some(TokenType::STATIC),
+            "import" => Some(TokenType::IMPORT),
+            "from" => Some(TokenType::FROM),
+            "as" => Some(TokenType::AS),
+            "export" => Some(TokenType::EXPORT),
+            "match" => Some(TokenType::MATCH),
+            "const" => Some(TokenType::CONST),
+            "in" => Some(TokenType::IN),
+            "yield" => Some(TokenType::YIELD),
+            "enum" => Some(TokenType::ENUM),
+            "trait" => Some(TokenType::TRAIT),
+            "with" => Some(TokenType::WITH),
             "break" => Some(TokenType::Break),
+            "continue" => Some(TokenType::Continue),
             _ => None,
         }
     }
@@ -6,14 +6,26 @@ use std::rc::Rc;
 pub enum Stmt {
     Block(Rc<BlockStmt>),
     Class(Rc<ClassStmt>),
+    Trait(Rc<TraitStmt>),
+    Enum(Rc<EnumStmt>),
     Break(Rc<BreakStmt>),
+    Continue(Rc<ContinueStmt>),
     Expression(Rc<ExpressionStmt>),
     Function(Rc<FunctionStmt>),
     If(Rc<IfStmt>),
     Print(Rc<PrintStmt>),
     Return(Rc<ReturnStmt>),
+    Throw(Rc<ThrowStmt>),
+    Yield(Rc<YieldStmt>),
+    Import(Rc<ImportStmt>),
+    Export(Rc<ExportStmt>),
+    Try(Rc<TryStmt>),
     Var(Rc<VarStmt>),
+    Const(Rc<ConstStmt>),
+    Destructure(Rc<DestructureStmt>),
+    MultiAssign(Rc<MultiAssignStmt>),
     While(Rc<WhileStmt>),
+    ForIn(Rc<ForInStmt>),
 }
 
 impl PartialEq for Stmt {
@@ -21,14 +33,26 @@ impl PartialEq for Stmt {
         match (self, other) {
             (Stmt::Block(a), Stmt::Block(b)) => Rc::ptr_eq(a, b),
             (Stmt::Class(a), Stmt::Class(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Trait(a), Stmt::Trait(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Enum(a), Stmt::Enum(b)) => Rc::ptr_eq(a, b),
             (Stmt::Break(a), Stmt::Break(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Continue(a), Stmt::Continue(b)) => Rc::ptr_eq(a, b),
             (Stmt::Expression(a), Stmt::Expression(b)) => Rc::ptr_eq(a, b),
             (Stmt::Function(a), Stmt::Function(b)) => Rc::ptr_eq(a, b),
             (Stmt::If(a), Stmt::If(b)) => Rc::ptr_eq(a, b),
             (Stmt::Print(a), Stmt::Print(b)) => Rc::ptr_eq(a, b),
             (Stmt::Return(a), Stmt::Return(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Throw(a), Stmt::Throw(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Yield(a), Stmt::Yield(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Import(a), Stmt::Import(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Export(a), Stmt::Export(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Try(a), Stmt::Try(b)) => Rc::ptr_eq(a, b),
             (Stmt::Var(a), Stmt::Var(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Const(a), Stmt::Const(b)) => Rc::ptr_eq(a, b),
+            (Stmt::Destructure(a), Stmt::Destructure(b)) => Rc::ptr_eq(a, b),
+            (Stmt::MultiAssign(a), Stmt::MultiAssign(b)) => Rc::ptr_eq(a, b),
             (Stmt::While(a), Stmt::While(b)) => Rc::ptr_eq(a, b),
+            (Stmt::ForIn(a), Stmt::ForIn(b)) => Rc::ptr_eq(a, b),
             _ => false,
         }
     }
@@ -43,14 +67,26 @@ impl Hash for Stmt {
     { match self { 
         Stmt::Block(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::Class(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Trait(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Enum(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::Break(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Continue(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::Expression(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::Function(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::If(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::Print(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::Return(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Throw(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Yield(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Import(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Export(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Try(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::Var(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Const(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::Destructure(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::MultiAssign(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         Stmt::While(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
+        Stmt::ForIn(a) => { hasher.write_usize(Rc::as_ptr(a) as usize); }
         }
     }
 }
@@ -60,14 +96,26 @@ impl Stmt {
         match self {
             Stmt::Block(v) => stmt_visitor.visit_block_stmt(wrapper, v),
             Stmt::Class(v) => stmt_visitor.visit_class_stmt(wrapper, v),
+            Stmt::Trait(v) => stmt_visitor.visit_trait_stmt(wrapper, v),
+            Stmt::Enum(v) => stmt_visitor.visit_enum_stmt(wrapper, v),
             Stmt::Break(v) => stmt_visitor.visit_break_stmt(wrapper, v),
+            Stmt::Continue(v) => stmt_visitor.visit_continue_stmt(wrapper, v),
             Stmt::Expression(v) => stmt_visitor.visit_expression_stmt(wrapper, v),
             Stmt::Function(v) => stmt_visitor.visit_function_stmt(wrapper, v),
             Stmt::If(v) => stmt_visitor.visit_if_stmt(wrapper, v),
             Stmt::Print(v) => stmt_visitor.visit_print_stmt(wrapper, v),
             Stmt::Return(v) => stmt_visitor.visit_return_stmt(wrapper, v),
+            Stmt::Throw(v) => stmt_visitor.visit_throw_stmt(wrapper, v),
+            Stmt::Yield(v) => stmt_visitor.visit_yield_stmt(wrapper, v),
+            Stmt::Import(v) => stmt_visitor.visit_import_stmt(wrapper, v),
+            Stmt::Export(v) => stmt_visitor.visit_export_stmt(wrapper, v),
+            Stmt::Try(v) => stmt_visitor.visit_try_stmt(wrapper, v),
             Stmt::Var(v) => stmt_visitor.visit_var_stmt(wrapper, v),
+            Stmt::Const(v) => stmt_visitor.visit_const_stmt(wrapper, v),
+            Stmt::Destructure(v) => stmt_visitor.visit_destructure_stmt(wrapper, v),
+            Stmt::MultiAssign(v) => stmt_visitor.visit_multiassign_stmt(wrapper, v),
             Stmt::While(v) => stmt_visitor.visit_while_stmt(wrapper, v),
+            Stmt::ForIn(v) => stmt_visitor.visit_forin_stmt(wrapper, v),
         }
     }
 }
@@ -79,13 +127,34 @@ pub struct BlockStmt {
 pub struct ClassStmt {
     pub name: Token,
     pub superclass: Option<Rc<Expr>>,
+    pub traits: Rc<Vec<Rc<Expr>>>,
     pub methods: Rc<Vec<Rc<Stmt>>>,
+    pub static_methods: Rc<Vec<Rc<Stmt>>>,
+    pub static_fields: Rc<Vec<Rc<Stmt>>>,
+    pub getters: Rc<Vec<Rc<Stmt>>>,
+    pub setters: Rc<Vec<Rc<Stmt>>>,
+}
+
+pub struct TraitStmt {
+    pub name: Token,
+    pub methods: Rc<Vec<Rc<Stmt>>>,
+    pub required: Rc<Vec<Token>>,
+}
+
+pub struct EnumStmt {
+    pub name: Token,
+    pub variants: Rc<Vec<Token>>,
+    pub fields: Rc<Vec<Option<Vec<Token>>>>,
 }
 
 pub struct BreakStmt {
     pub token: Token,
 }
 
+pub struct ContinueStmt {
+    pub token: Token,
+}
+
 pub struct ExpressionStmt {
     pub expression: Rc<Expr>,
 }
@@ -93,7 +162,10 @@ pub struct ExpressionStmt {
 pub struct FunctionStmt {
     pub name: Token,
     pub params: Rc<Vec<Token>>,
+    pub defaults: Rc<Vec<Option<Rc<Expr>>>>,
+    pub rest: Option<Token>,
     pub body: Rc<Vec<Rc<Stmt>>>,
+    pub is_generator: bool,
 }
 
 pub struct IfStmt {
@@ -111,26 +183,92 @@ pub struct ReturnStmt {
     pub value: Option<Rc<Expr>>,
 }
 
+pub struct ThrowStmt {
+    pub keyword: Token,
+    pub value: Rc<Expr>,
+}
+
+pub struct YieldStmt {
+    pub keyword: Token,
+    pub value: Option<Rc<Expr>>,
+}
+
+pub struct ImportStmt {
+    pub keyword: Token,
+    pub path: Token,
+    pub alias: Option<Token>,
+    pub names: Rc<Vec<Token>>,
+}
+
+pub struct ExportStmt {
+    pub keyword: Token,
+    pub declaration: Rc<Stmt>,
+}
+
+pub struct TryStmt {
+    pub body: Rc<Vec<Rc<Stmt>>>,
+    pub catch_name: Option<Token>,
+    pub catch_body: Option<Rc<Vec<Rc<Stmt>>>>,
+    pub finally_body: Option<Rc<Vec<Rc<Stmt>>>>,
+}
+
 pub struct VarStmt {
     pub name: Token,
     pub initializer: Option<Rc<Expr>>,
 }
 
+pub struct ConstStmt {
+    pub name: Token,
+    pub initializer: Rc<Expr>,
+}
+
+pub struct DestructureStmt {
+    pub pattern: Token,
+    pub names: Vec<Token>,
+    pub initializer: Rc<Expr>,
+}
+
+pub struct MultiAssignStmt {
+    pub targets: Vec<Rc<Expr>>,
+    pub equals: Token,
+    pub values: Vec<Rc<Expr>>,
+}
+
 pub struct WhileStmt {
     pub condition: Rc<Expr>,
     pub body: Rc<Stmt>,
+    pub increment: Option<Rc<Expr>>,
+}
+
+pub struct ForInStmt {
+    pub name: Token,
+    pub keyword: Token,
+    pub iterable: Rc<Expr>,
+    pub body: Rc<Stmt>,
 }
 
 pub trait StmtVisitor<T> {
     fn visit_block_stmt(&self, wrapper: Rc<Stmt>, stmt: &BlockStmt) -> Result<T, LoxResult>;
     fn visit_class_stmt(&self, wrapper: Rc<Stmt>, stmt: &ClassStmt) -> Result<T, LoxResult>;
+    fn visit_trait_stmt(&self, wrapper: Rc<Stmt>, stmt: &TraitStmt) -> Result<T, LoxResult>;
+    fn visit_enum_stmt(&self, wrapper: Rc<Stmt>, stmt: &EnumStmt) -> Result<T, LoxResult>;
     fn visit_break_stmt(&self, wrapper: Rc<Stmt>, stmt: &BreakStmt) -> Result<T, LoxResult>;
+    fn visit_continue_stmt(&self, wrapper: Rc<Stmt>, stmt: &ContinueStmt) -> Result<T, LoxResult>;
     fn visit_expression_stmt(&self, wrapper: Rc<Stmt>, stmt: &ExpressionStmt) -> Result<T, LoxResult>;
     fn visit_function_stmt(&self, wrapper: Rc<Stmt>, stmt: &FunctionStmt) -> Result<T, LoxResult>;
     fn visit_if_stmt(&self, wrapper: Rc<Stmt>, stmt: &IfStmt) -> Result<T, LoxResult>;
     fn visit_print_stmt(&self, wrapper: Rc<Stmt>, stmt: &PrintStmt) -> Result<T, LoxResult>;
     fn visit_return_stmt(&self, wrapper: Rc<Stmt>, stmt: &ReturnStmt) -> Result<T, LoxResult>;
+    fn visit_throw_stmt(&self, wrapper: Rc<Stmt>, stmt: &ThrowStmt) -> Result<T, LoxResult>;
+    fn visit_yield_stmt(&self, wrapper: Rc<Stmt>, stmt: &YieldStmt) -> Result<T, LoxResult>;
+    fn visit_import_stmt(&self, wrapper: Rc<Stmt>, stmt: &ImportStmt) -> Result<T, LoxResult>;
+    fn visit_export_stmt(&self, wrapper: Rc<Stmt>, stmt: &ExportStmt) -> Result<T, LoxResult>;
+    fn visit_try_stmt(&self, wrapper: Rc<Stmt>, stmt: &TryStmt) -> Result<T, LoxResult>;
     fn visit_var_stmt(&self, wrapper: Rc<Stmt>, stmt: &VarStmt) -> Result<T, LoxResult>;
+    fn visit_const_stmt(&self, wrapper: Rc<Stmt>, stmt: &ConstStmt) -> Result<T, LoxResult>;
+    fn visit_destructure_stmt(&self, wrapper: Rc<Stmt>, stmt: &DestructureStmt) -> Result<T, LoxResult>;
+    fn visit_multiassign_stmt(&self, wrapper: Rc<Stmt>, stmt: &MultiAssignStmt) -> Result<T, LoxResult>;
     fn visit_while_stmt(&self, wrapper: Rc<Stmt>, stmt: &WhileStmt) -> Result<T, LoxResult>;
+    fn visit_forin_stmt(&self, wrapper: Rc<Stmt>, stmt: &ForInStmt) -> Result<T, LoxResult>;
 }
 